@@ -0,0 +1,485 @@
+use crate::models::money::Money;
+use crate::models::rate::RateParseErr;
+use crate::models::transaction::TransactionErr;
+use prettytable::{row, Cell, Row, Table};
+
+pub use crate::models::rate::Rate;
+
+/// Share of gross monthly income that housing costs may take.
+const HOUSING_RATIO: i64 = 28;
+/// Share of gross monthly income that housing plus other debt payments may take.
+const TOTAL_DEBT_RATIO: i64 = 36;
+
+/// Longest term the calculators accept, 100 years.
+const MAX_TERM_MONTHS: u32 = 1200;
+
+#[derive(Debug, thiserror::Error)]
+pub enum CalcErr {
+    #[error("The term must be at least one month")]
+    ZeroTerm,
+    #[error("The term can be at most 100 years")]
+    TermTooLong,
+    #[error("The amount must be greater than zero")]
+    NonPositiveAmount,
+    #[error("Debts, costs and the down payment can't be negative")]
+    NegativeAmount,
+    #[error("The down payment covers the whole price")]
+    NoLoanNeeded,
+    #[error("The result is too large")]
+    TooLarge,
+    #[error(transparent)]
+    Amount(#[from] TransactionErr),
+    #[error(transparent)]
+    Rate(#[from] RateParseErr),
+}
+
+/// Months in a term of `years`.
+pub fn term_months(years: u32) -> Result<u32, CalcErr> {
+    years
+        .checked_mul(12)
+        .filter(|&months| months <= MAX_TERM_MONTHS)
+        .ok_or(CalcErr::TermTooLong)
+}
+
+fn check_term(months: u32) -> Result<(), CalcErr> {
+    match months {
+        0 => Err(CalcErr::ZeroTerm),
+        m if m > MAX_TERM_MONTHS => Err(CalcErr::TermTooLong),
+        _ => Ok(()),
+    }
+}
+
+/// The level monthly payment that repays `principal` over `months`,
+/// rounded to the cent as lenders quote it.
+pub fn monthly_payment(principal: Money, rate: Rate, months: u32) -> Result<Money, CalcErr> {
+    check_term(months)?;
+    if principal <= Money::ZERO {
+        return Err(CalcErr::NonPositiveAmount);
+    }
+
+    let (cents, r) = (principal.cents() as f64, rate.monthly_fraction());
+    let payment = if r == 0.0 {
+        cents / months as f64
+    } else {
+        cents * r / (1.0 - (1.0 + r).powi(-(months as i32)))
+    };
+    // Casting anything at or past i64::MAX would saturate rather than fail
+    if payment >= i64::MAX as f64 {
+        return Err(CalcErr::TooLarge);
+    }
+    Ok(Money::from_cents(payment.round().max(1.0) as i64))
+}
+
+/// One month of a loan's repayment.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AmortizationRow {
+    pub month: u32,
+    pub payment: Money,
+    pub interest: Money,
+    pub principal: Money,
+    pub balance: Money,
+}
+
+/// The month-by-month schedule for repaying `principal` over `months`
+/// with equal payments. Interest is rounded to the cent each month, so the
+/// last payment settles whatever is left and may differ by a few cents.
+/// Fails with [`CalcErr::TooLarge`] when the total repaid doesn't fit.
+pub fn amortize(
+    principal: Money,
+    rate: Rate,
+    months: u32,
+) -> Result<Vec<AmortizationRow>, CalcErr> {
+    let payment = monthly_payment(principal, rate, months)?;
+    let mut balance = principal;
+    let mut total = Money::ZERO;
+    let mut rows = Vec::new();
+
+    for month in 1..=months {
+        let interest = rate.monthly_interest(balance);
+        let owed = balance.checked_add(interest).ok_or(CalcErr::TooLarge)?;
+        let paid = if month == months {
+            owed
+        } else {
+            payment.min(owed)
+        };
+        total = total.checked_add(paid).ok_or(CalcErr::TooLarge)?;
+        balance = owed - paid;
+        rows.push(AmortizationRow {
+            month,
+            payment: paid,
+            interest,
+            principal: paid - interest,
+            balance,
+        });
+        if balance == Money::ZERO {
+            break;
+        }
+    }
+    Ok(rows)
+}
+
+/// Savings at the end of one year of a projection.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SavingsRow {
+    pub year: u32,
+    /// Everything put in so far, including the starting amount
+    pub contributed: Money,
+    /// Interest earned so far
+    pub interest: Money,
+    pub balance: Money,
+}
+
+/// Grow `initial` with `monthly` added at the end of each month and
+/// interest compounded monthly, reporting the position after each year.
+pub fn project_savings(
+    initial: Money,
+    monthly: Money,
+    rate: Rate,
+    years: u32,
+) -> Result<Vec<SavingsRow>, CalcErr> {
+    term_months(years)?;
+    let mut balance = initial;
+    let mut contributed = initial;
+    let mut interest = Money::ZERO;
+    let mut rows = Vec::new();
+
+    for year in 1..=years {
+        for _ in 0..12 {
+            let earned = rate.monthly_interest(balance);
+            interest = interest.checked_add(earned).ok_or(CalcErr::TooLarge)?;
+            contributed = contributed.checked_add(monthly).ok_or(CalcErr::TooLarge)?;
+            balance = balance
+                .checked_add(earned)
+                .and_then(|b| b.checked_add(monthly))
+                .ok_or(CalcErr::TooLarge)?;
+        }
+        rows.push(SavingsRow {
+            year,
+            contributed,
+            interest,
+            balance,
+        });
+    }
+    Ok(rows)
+}
+
+/// How much house an income supports under the 28/36 rule.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Affordability {
+    /// Most that can go to housing each month, including `costs`
+    pub housing_limit: Money,
+    /// Monthly taxes, insurance and dues on top of the loan payment
+    pub costs: Money,
+    /// Largest loan whose payment fits under the limit
+    pub max_loan: Money,
+    pub max_price: Money,
+}
+
+impl Affordability {
+    pub fn max_payment(&self) -> Money {
+        (self.housing_limit - self.costs).max(Money::ZERO)
+    }
+}
+
+/// Apply the 28/36 rule to a gross monthly `income`: housing may take 28%
+/// of it, and housing plus other monthly `debts` 36%.
+pub fn affordability(
+    income: Money,
+    debts: Money,
+    costs: Money,
+    down: Money,
+    rate: Rate,
+    months: u32,
+) -> Result<Affordability, CalcErr> {
+    check_term(months)?;
+    if income <= Money::ZERO {
+        return Err(CalcErr::NonPositiveAmount);
+    }
+    if debts.is_negative() || costs.is_negative() || down.is_negative() {
+        return Err(CalcErr::NegativeAmount);
+    }
+
+    let housing_limit = income
+        .mul_ratio(HOUSING_RATIO, 100)
+        .min(income.mul_ratio(TOTAL_DEBT_RATIO, 100) - debts)
+        .max(Money::ZERO);
+    let mut result = Affordability {
+        housing_limit,
+        costs,
+        max_loan: Money::ZERO,
+        max_price: down,
+    };
+
+    // Largest loan in whole cents whose payment still fits
+    let max_payment = result.max_payment();
+    let mut low = 0;
+    let mut high = max_payment
+        .cents()
+        .checked_mul(months as i64)
+        .ok_or(CalcErr::TooLarge)?;
+    while low < high {
+        let mid = low + (high - low + 1) / 2;
+        if monthly_payment(Money::from_cents(mid), rate, months)? <= max_payment {
+            low = mid;
+        } else {
+            high = mid - 1;
+        }
+    }
+    result.max_loan = Money::from_cents(low);
+    result.max_price = down.checked_add(result.max_loan).ok_or(CalcErr::TooLarge)?;
+    Ok(result)
+}
+
+/// The monthly cost of buying at a given price.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PriceCheck {
+    pub price: Money,
+    pub loan: Money,
+    pub payment: Money,
+    pub affordable: bool,
+}
+
+pub fn check_price(
+    limits: &Affordability,
+    price: Money,
+    down: Money,
+    rate: Rate,
+    months: u32,
+) -> Result<PriceCheck, CalcErr> {
+    let loan = price.checked_sub(down).ok_or(CalcErr::NoLoanNeeded)?;
+    if loan <= Money::ZERO {
+        return Err(CalcErr::NoLoanNeeded);
+    }
+    let payment = monthly_payment(loan, rate, months)?;
+    let housing = payment.checked_add(limits.costs).ok_or(CalcErr::TooLarge)?;
+    Ok(PriceCheck {
+        price,
+        loan,
+        payment,
+        affordable: housing <= limits.housing_limit,
+    })
+}
+
+pub fn amortization_table(rows: &[AmortizationRow]) -> Table {
+    let mut table = Table::new();
+
+    table.add_row(row!["Month", "Payment", "Interest", "Principal", "Balance"]);
+
+    for r in rows {
+        table.add_row(Row::new(vec![
+            Cell::new(&r.month.to_string()),
+            Cell::new(&format!("${}", r.payment)),
+            Cell::new(&format!("${}", r.interest)),
+            Cell::new(&format!("${}", r.principal)),
+            Cell::new(&format!("${}", r.balance)),
+        ]));
+    }
+    table
+}
+
+pub fn print_amortization(principal: Money, rate: Rate, rows: &[AmortizationRow]) {
+    crate::cli::display::page(&amortization_table(rows).to_string());
+
+    let interest: Money = rows.iter().map(|r| r.interest).sum();
+    if let Some(first) = rows.first() {
+        println!(
+            "${} at {} over {} months: ${} a month, ${} in interest, ${} in total",
+            principal,
+            rate,
+            rows.len(),
+            first.payment,
+            interest,
+            principal + interest
+        );
+    }
+}
+
+pub fn savings_table(rows: &[SavingsRow]) -> Table {
+    let mut table = Table::new();
+
+    table.add_row(row!["Year", "Contributed", "Interest", "Balance"]);
+
+    for r in rows {
+        table.add_row(Row::new(vec![
+            Cell::new(&r.year.to_string()),
+            Cell::new(&format!("${}", r.contributed)),
+            Cell::new(&format!("${}", r.interest)),
+            Cell::new(&format!("${}", r.balance)),
+        ]));
+    }
+    table
+}
+
+pub fn print_affordability(limits: &Affordability, check: Option<&PriceCheck>) {
+    let mut table = Table::new();
+
+    table.add_row(row!["Housing limit", format!("${}", limits.housing_limit)]);
+    table.add_row(row![
+        "Taxes, insurance and dues",
+        format!("${}", limits.costs)
+    ]);
+    table.add_row(row![
+        "Max loan payment",
+        format!("${}", limits.max_payment())
+    ]);
+    table.add_row(row!["Max loan", format!("${}", limits.max_loan)]);
+    table.add_row(row!["Max price", format!("${}", limits.max_price)]);
+
+    if let Some(check) = check {
+        let verdict = if check.affordable {
+            Cell::new("Affordable")
+        } else {
+            Cell::new("Over the limit").style_spec("Fr")
+        };
+        table.add_row(row![
+            format!("Loan at ${}", check.price),
+            format!("${}", check.loan)
+        ]);
+        table.add_row(row!["Loan payment", format!("${}", check.payment)]);
+        table.add_row(Row::new(vec![
+            Cell::new("Monthly housing cost"),
+            Cell::new(&format!("${}", check.payment + limits.costs)),
+        ]));
+        table.add_row(Row::new(vec![Cell::new("Verdict"), verdict]));
+    }
+    table.printstd();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{money, rate};
+
+    #[test]
+    fn test_monthly_payment_matches_annuity_formula() {
+        // $200,000 at 6% for 30 years is $1,199.10 a month
+        let payment = monthly_payment(money("200000"), rate("6"), 360).unwrap();
+        assert_eq!(payment, money("1199.10"));
+        // No interest splits the principal evenly
+        assert_eq!(
+            monthly_payment(money("1000"), rate("0"), 3).unwrap(),
+            money("333.33")
+        );
+        assert!(matches!(
+            monthly_payment(money("1000"), rate("5"), 0),
+            Err(CalcErr::ZeroTerm)
+        ));
+    }
+
+    #[test]
+    fn test_amortize_pays_off_exactly() {
+        let rows = amortize(money("10000"), rate("5"), 24).unwrap();
+
+        assert_eq!(rows.len(), 24);
+        assert_eq!(rows[0].interest, money("41.67"));
+        assert_eq!(rows[0].principal, rows[0].payment - rows[0].interest);
+        assert_eq!(rows.last().unwrap().balance, Money::ZERO);
+        let drift = rows.last().unwrap().payment - rows[0].payment;
+        assert!(drift.abs() < money("1.00"));
+        let principal: Money = rows.iter().map(|r| r.principal).sum();
+        assert_eq!(principal, money("10000"));
+    }
+
+    #[test]
+    fn test_project_savings() {
+        let rows = project_savings(money("1000"), money("100"), rate("0"), 2).unwrap();
+        assert_eq!(rows[1].contributed, money("3400"));
+        assert_eq!(rows[1].balance, money("3400"));
+
+        let rows = project_savings(money("1000"), Money::ZERO, rate("12"), 1).unwrap();
+        // 1% a month compounded, with each month's interest rounded to the cent
+        assert_eq!(rows[0].balance, money("1126.84"));
+        assert_eq!(rows[0].interest, money("126.84"));
+    }
+
+    #[test]
+    fn test_term_is_capped() {
+        assert_eq!(term_months(30).unwrap(), 360);
+        assert!(matches!(term_months(101), Err(CalcErr::TermTooLong)));
+        assert!(matches!(term_months(u32::MAX), Err(CalcErr::TermTooLong)));
+        assert!(matches!(
+            amortize(money("1000"), rate("5"), 1201),
+            Err(CalcErr::TermTooLong)
+        ));
+        assert!(matches!(
+            project_savings(money("1000"), money("100"), rate("5"), 400_000_000),
+            Err(CalcErr::TermTooLong)
+        ));
+    }
+
+    #[test]
+    fn test_affordability_uses_tighter_ratio() {
+        let limits = affordability(
+            money("10000"),
+            money("1000"),
+            money("300"),
+            money("50000"),
+            rate("6"),
+            360,
+        )
+        .unwrap();
+
+        // 28% is $2,800 but 36% less $1,000 of debts is $2,600
+        assert_eq!(limits.housing_limit, money("2600"));
+        assert_eq!(limits.max_payment(), money("2300"));
+        assert!(monthly_payment(limits.max_loan, rate("6"), 360).unwrap() <= money("2300"));
+        let one_more = limits.max_loan + Money::from_cents(100);
+        assert!(monthly_payment(one_more, rate("6"), 360).unwrap() > money("2300"));
+        assert_eq!(limits.max_price, limits.max_loan + money("50000"));
+
+        let fits = check_price(&limits, limits.max_price, money("50000"), rate("6"), 360).unwrap();
+        assert!(fits.affordable);
+        let over = check_price(&limits, money("500000"), money("50000"), rate("6"), 360).unwrap();
+        assert!(!over.affordable);
+        assert!(matches!(
+            check_price(&limits, money("40000"), money("50000"), rate("6"), 360),
+            Err(CalcErr::NoLoanNeeded)
+        ));
+    }
+
+    #[test]
+    fn test_negative_affordability_inputs_are_rejected() {
+        let afford = |debts, costs, down| {
+            affordability(
+                money("9000"),
+                money(debts),
+                money(costs),
+                money(down),
+                rate("6"),
+                360,
+            )
+        };
+        assert!(afford("0", "0", "0").is_ok());
+        for (debts, costs, down) in [("-1", "0", "0"), ("0", "-1", "0"), ("0", "0", "-1")] {
+            assert!(matches!(
+                afford(debts, costs, down),
+                Err(CalcErr::NegativeAmount)
+            ));
+        }
+    }
+
+    #[test]
+    fn test_overflow_is_an_error() {
+        assert!(matches!(
+            project_savings(Money::ZERO, money("1000"), rate("30"), 100),
+            Err(CalcErr::TooLarge)
+        ));
+        assert!(matches!(
+            amortize(money("90000000000000000"), rate("1000"), 1200),
+            Err(CalcErr::TooLarge)
+        ));
+        assert!(matches!(
+            monthly_payment(money("90000000000000000"), rate("1000"), 1),
+            Err(CalcErr::TooLarge)
+        ));
+        let limits = affordability(
+            money("90000000000000000"),
+            Money::ZERO,
+            Money::ZERO,
+            Money::ZERO,
+            rate("5"),
+            360,
+        );
+        assert!(matches!(limits, Err(CalcErr::TooLarge)));
+    }
+}
@@ -0,0 +1,457 @@
+use crate::models::account::{self, Account, AccountKind};
+use crate::models::money::Money;
+use crate::models::query::TransactionQuery;
+use crate::models::recurring::Schedule;
+use crate::models::transaction::Transaction;
+use crate::utils::date_utils::YearMonth;
+use chrono::{Duration, NaiveDate};
+use prettytable::{row, Cell, Row, Table};
+
+/// Whole months of history used to work out each category's run-rate.
+const RUN_RATE_MONTHS: i32 = 3;
+
+/// Money expected to move on a future date.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Flow {
+    pub date: NaiveDate,
+    pub account: Option<String>,
+    /// Positive for money coming in
+    pub amount: Money,
+    /// Shown for scheduled items; run-rate flows have none
+    pub label: Option<String>,
+}
+
+/// Every occurrence of the recurring schedules from `from` to `to`, except
+/// for schedules on a closed account, which are no longer posted.
+pub fn scheduled_flows(
+    schedules: &[Schedule],
+    accounts: &[Account],
+    from: NaiveDate,
+    to: NaiveDate,
+) -> Vec<Flow> {
+    let mut flows = Vec::new();
+    for schedule in schedules.iter().filter(|s| !s.account_closed(accounts)) {
+        let amount = if schedule.category.is_income() {
+            schedule.amount
+        } else {
+            -schedule.amount
+        };
+        let label = match &schedule.note {
+            Some(note) => format!("{} ({})", schedule.category, note),
+            None => schedule.category.to_string(),
+        };
+        for date in schedule.occurrences_between(from, to) {
+            flows.push(Flow {
+                date,
+                account: schedule.account.clone(),
+                amount,
+                label: Some(label.clone()),
+            });
+        }
+    }
+    flows
+}
+
+/// Spending and income that isn't scheduled, spread evenly over each day
+/// from `from` to `to` at the pace of the last few whole months.
+///
+/// Transfers, transactions posted from a schedule and sinking fund payments
+/// are left out since they are either scheduled or planned for separately.
+pub fn run_rate_flows(transactions: &[Transaction], today: NaiveDate, to: NaiveDate) -> Vec<Flow> {
+    let this_month = YearMonth::of(today);
+    let window_start = this_month.add_months(-RUN_RATE_MONTHS).first_day();
+    let window_end = this_month.add_months(-1).last_day();
+    let window_days = (window_end - window_start).num_days() + 1;
+
+    // net unscheduled amount per account over the window
+    let mut totals: Vec<(Option<String>, Money)> = Vec::new();
+    let window = TransactionQuery::between(window_start, window_end).without_transfers();
+    for t in window.apply(transactions) {
+        if t.occurrence().is_some() || t.fund().is_some() {
+            continue;
+        }
+        let account = t.account().map(str::to_string);
+        match totals.iter_mut().find(|(a, _)| *a == account) {
+            Some((_, total)) => *total += t.signed_amount(),
+            None => totals.push((account, t.signed_amount())),
+        }
+    }
+
+    let mut flows = Vec::new();
+    let mut date = today + Duration::days(1);
+    while date <= to {
+        let day = (date - today).num_days();
+        for (account, total) in &totals {
+            // difference of running totals so rounding never drifts
+            let amount = total.mul_ratio(day, window_days) - total.mul_ratio(day - 1, window_days);
+            if amount != Money::ZERO {
+                flows.push(Flow {
+                    date,
+                    account: account.clone(),
+                    amount,
+                    label: None,
+                });
+            }
+        }
+        date += Duration::days(1);
+    }
+    flows
+}
+
+/// One projected day.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ForecastDay {
+    pub date: NaiveDate,
+    pub change: Money,
+    pub balance: Money,
+    pub labels: Vec<String>,
+}
+
+/// The first day a balance is projected to be below zero.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Shortfall {
+    /// Account name, or `None` for the combined balance
+    pub account: Option<String>,
+    pub date: NaiveDate,
+    pub balance: Money,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Forecast {
+    pub start_balance: Money,
+    pub days: Vec<ForecastDay>,
+    /// Projected closing balance of each open account
+    pub accounts: Vec<(String, Money)>,
+    pub shortfalls: Vec<Shortfall>,
+}
+
+impl Forecast {
+    pub fn end_balance(&self) -> Money {
+        self.days
+            .last()
+            .map(|d| d.balance)
+            .unwrap_or(self.start_balance)
+    }
+}
+
+/// Project balances day by day from tomorrow to `to`.
+///
+/// The combined balance starts from the open accounts' balances at the end
+/// of `today`, or from the ledger up to `today` when no accounts are set up.
+/// Flows for an account that isn't open only move the combined balance.
+///
+/// A shortfall is noted on the first day each balance is below zero, even
+/// one that is below zero already. Credit and loan accounts normally carry a
+/// negative balance, so they are never flagged on their own.
+pub fn project(
+    accounts: &[Account],
+    transactions: &[Transaction],
+    flows: &[Flow],
+    today: NaiveDate,
+    to: NaiveDate,
+) -> Forecast {
+    let open: Vec<&Account> = accounts.iter().filter(|a| !a.closed).collect();
+    let mut balances: Vec<(String, Money)> = open
+        .iter()
+        .map(|a| (a.name.clone(), account::balance_on(a, transactions, today)))
+        .collect();
+    let start_balance = if open.is_empty() {
+        transactions
+            .iter()
+            .filter(|t| t.date() <= today)
+            .map(|t| t.signed_amount())
+            .sum()
+    } else {
+        balances.iter().map(|(_, b)| *b).sum()
+    };
+
+    let mut shortfalls: Vec<Shortfall> = Vec::new();
+    let mut note_shortfall = |account: Option<&str>, date: NaiveDate, balance: Money| {
+        let account = account.map(str::to_string);
+        if balance.is_negative() && !shortfalls.iter().any(|s| s.account == account) {
+            shortfalls.push(Shortfall {
+                account,
+                date,
+                balance,
+            });
+        }
+    };
+
+    let mut days = Vec::new();
+    let mut balance = start_balance;
+    let mut date = today + Duration::days(1);
+    while date <= to {
+        let mut change = Money::ZERO;
+        let mut labels = Vec::new();
+        for flow in flows.iter().filter(|f| f.date == date) {
+            change += flow.amount;
+            if let Some(label) = &flow.label {
+                labels.push(label.clone());
+            }
+            let name = flow.account.as_deref().unwrap_or_default();
+            if let Some((_, account_balance)) = balances
+                .iter_mut()
+                .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            {
+                *account_balance += flow.amount;
+            }
+        }
+        for ((name, account_balance), account) in balances.iter().zip(&open) {
+            if !matches!(account.kind, AccountKind::Credit | AccountKind::Loan) {
+                note_shortfall(Some(name.as_str()), date, *account_balance);
+            }
+        }
+        balance += change;
+        note_shortfall(None, date, balance);
+
+        days.push(ForecastDay {
+            date,
+            change,
+            balance,
+            labels,
+        });
+        date += Duration::days(1);
+    }
+
+    Forecast {
+        start_balance,
+        days,
+        accounts: balances,
+        shortfalls,
+    }
+}
+
+pub fn forecast_table(forecast: &Forecast) -> Table {
+    let mut table = Table::new();
+
+    table.add_row(row!["Date", "Change", "Balance", "Scheduled"]);
+
+    for day in &forecast.days {
+        let balance = format!("${}", day.balance);
+        let balance_cell = if day.balance.is_negative() {
+            Cell::new(&balance).style_spec("Fr")
+        } else {
+            Cell::new(&balance)
+        };
+        table.add_row(Row::new(vec![
+            Cell::new(&day.date.to_string()),
+            Cell::new(&format!("${}", day.change)),
+            balance_cell,
+            Cell::new(&day.labels.join(", ")),
+        ]));
+    }
+    table
+}
+
+pub fn print_forecast(forecast: &Forecast, to: NaiveDate) {
+    crate::cli::display::page(&forecast_table(forecast).to_string());
+
+    println!(
+        "Balance now ${}, projected ${} on {}",
+        forecast.start_balance,
+        forecast.end_balance(),
+        to
+    );
+    for (name, balance) in &forecast.accounts {
+        println!("  {}: ${}", name, balance);
+    }
+    for shortfall in &forecast.shortfalls {
+        let whose = match &shortfall.account {
+            Some(name) => name.as_str(),
+            None => "The combined balance",
+        };
+        println!(
+            "Warning: {} is projected to be below zero on {} (${})",
+            whose, shortfall.date, shortfall.balance
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::recurring::Frequency;
+    use crate::test_util::{day, money, transaction};
+
+    fn rent() -> Schedule {
+        Schedule::new(
+            1,
+            day(2024, 1, 1),
+            Frequency::Monthly,
+            None,
+            None,
+            money("1000"),
+            "Bills".parse().unwrap(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_scheduled_flows_are_signed() {
+        let mut rent = rent();
+        rent.account = Some("Checking".to_string());
+        let flows = scheduled_flows(&[rent], &[], day(2024, 3, 2), day(2024, 5, 31));
+
+        assert_eq!(flows.len(), 2);
+        assert_eq!(flows[0].date, day(2024, 4, 1));
+        assert_eq!(flows[0].amount, money("-1000"));
+        assert_eq!(flows[0].account.as_deref(), Some("Checking"));
+    }
+
+    #[test]
+    fn test_scheduled_flows_leave_out_closed_accounts() {
+        let mut closed = Account::new("Old", AccountKind::Checking, Money::ZERO, "USD").unwrap();
+        closed.closed = true;
+        let mut rent = rent();
+        rent.account = Some("old".to_string());
+
+        let flows = scheduled_flows(&[rent], &[closed], day(2024, 3, 2), day(2024, 5, 31));
+        assert!(flows.is_empty());
+    }
+
+    #[test]
+    fn test_run_rate_spreads_recent_spending() {
+        // 91 days in Dec-Feb, 910 spent: 10 a day
+        let transactions = [
+            transaction(1, "12/10/2023", "300", "Food"),
+            transaction(2, "01/10/2024", "310", "Food"),
+            transaction(3, "02/10/2024", "300", "Food"),
+            // outside the window or planned elsewhere
+            transaction(4, "11/10/2023", "999", "Food"),
+            transaction(5, "02/11/2024", "500", "Bills").with_fund(Some("Tax".to_string())),
+        ];
+
+        let flows = run_rate_flows(&transactions, day(2024, 3, 10), day(2024, 3, 19));
+
+        let total: Money = flows.iter().map(|f| f.amount).sum();
+        assert_eq!(flows.len(), 9);
+        assert_eq!(total, money("-90"));
+        assert!(flows.iter().all(|f| f.label.is_none()));
+    }
+
+    #[test]
+    fn test_project_flags_first_negative_day() {
+        let accounts = [
+            Account::new("Checking", AccountKind::Checking, money("1200"), "USD").unwrap(),
+            Account::new("Savings", AccountKind::Savings, money("5000"), "USD").unwrap(),
+        ];
+        let mut rent = rent();
+        rent.account = Some("checking".to_string());
+        let mut flows = scheduled_flows(&[rent], &[], day(2024, 3, 16), day(2024, 5, 31));
+        flows.push(Flow {
+            date: day(2024, 3, 20),
+            account: Some("Checking".to_string()),
+            amount: money("-300"),
+            label: None,
+        });
+
+        let forecast = project(&accounts, &[], &flows, day(2024, 3, 15), day(2024, 5, 31));
+
+        assert_eq!(forecast.start_balance, money("6200"));
+        assert_eq!(forecast.end_balance(), money("3900"));
+        assert_eq!(forecast.days.len(), 77);
+        assert_eq!(forecast.accounts[0].1, money("-1100"));
+        assert_eq!(
+            forecast.shortfalls,
+            vec![Shortfall {
+                account: Some("Checking".to_string()),
+                date: day(2024, 4, 1),
+                balance: money("-100"),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_project_ignores_credit_cards_already_below_zero() {
+        let accounts = [
+            Account::new("Checking", AccountKind::Checking, money("2000"), "USD").unwrap(),
+            Account::new("Visa", AccountKind::Credit, money("-500"), "USD").unwrap(),
+        ];
+        let flows = [
+            Flow {
+                date: day(2024, 3, 16),
+                account: Some("Visa".to_string()),
+                amount: money("-200"),
+                label: None,
+            },
+            Flow {
+                date: day(2024, 3, 20),
+                account: Some("Checking".to_string()),
+                amount: money("-1000"),
+                label: None,
+            },
+        ];
+
+        let forecast = project(&accounts, &[], &flows, day(2024, 3, 15), day(2024, 3, 31));
+
+        assert_eq!(forecast.accounts[1].1, money("-700"));
+        assert_eq!(forecast.end_balance(), money("300"));
+        assert!(forecast.shortfalls.is_empty());
+    }
+
+    #[test]
+    fn test_project_flags_account_already_below_zero() {
+        let accounts = [
+            Account::new("Checking", AccountKind::Checking, money("-50"), "USD").unwrap(),
+            Account::new("Savings", AccountKind::Savings, money("5000"), "USD").unwrap(),
+        ];
+        let flows = [Flow {
+            date: day(2024, 3, 20),
+            account: Some("Checking".to_string()),
+            amount: money("-100"),
+            label: None,
+        }];
+
+        let forecast = project(&accounts, &[], &flows, day(2024, 3, 15), day(2024, 3, 31));
+
+        assert_eq!(
+            forecast.shortfalls,
+            vec![Shortfall {
+                account: Some("Checking".to_string()),
+                date: day(2024, 3, 16),
+                balance: money("-50"),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_project_starts_from_balance_today() {
+        let accounts =
+            [Account::new("Checking", AccountKind::Checking, money("100"), "USD").unwrap()];
+        let transactions = [transaction(1, "03/20/2024", "500", "Salary")
+            .with_account(Some("Checking".to_string()))];
+
+        let forecast = project(
+            &accounts,
+            &transactions,
+            &[],
+            day(2024, 3, 15),
+            day(2024, 3, 31),
+        );
+        assert_eq!(forecast.start_balance, money("100"));
+
+        let forecast = project(&[], &transactions, &[], day(2024, 3, 15), day(2024, 3, 31));
+        assert_eq!(forecast.start_balance, Money::ZERO);
+    }
+
+    #[test]
+    fn test_project_without_accounts_uses_ledger() {
+        let transactions = [
+            transaction(1, "03/01/2024", "500", "Salary"),
+            transaction(2, "03/02/2024", "200", "Food"),
+        ];
+        let flows = scheduled_flows(&[rent()], &[], day(2024, 3, 16), day(2024, 4, 30));
+
+        let forecast = project(
+            &[],
+            &transactions,
+            &flows,
+            day(2024, 3, 15),
+            day(2024, 4, 30),
+        );
+
+        assert_eq!(forecast.start_balance, money("300"));
+        assert_eq!(forecast.shortfalls[0].account, None);
+        assert_eq!(forecast.shortfalls[0].date, day(2024, 4, 1));
+    }
+}
@@ -1,3 +1,6 @@
 pub mod calculator;
+pub mod forecast;
+pub mod payoff;
 pub mod reports;
+pub mod subscriptions;
 pub mod trends;
@@ -0,0 +1,405 @@
+use crate::models::debt::{Debt, DebtErr};
+use crate::models::money::Money;
+use crate::utils::date_utils::YearMonth;
+use prettytable::{row, Cell, Row, Table};
+use std::cmp::Reverse;
+
+/// Longest plan simulated before giving up on it ever finishing.
+const MAX_MONTHS: usize = 1200;
+
+#[derive(Debug, thiserror::Error)]
+pub enum PayoffErr {
+    #[error("There are no debts to plan for")]
+    NoDebts,
+    #[error("The extra payment can't be negative")]
+    NegativeExtra,
+    #[error("The debts are too large to plan")]
+    TooLarge,
+    #[error(transparent)]
+    Debt(#[from] DebtErr),
+    #[error("The {0} plan never pays the debts off; the payments don't outpace the interest")]
+    NeverPaidOff(String),
+}
+
+/// Which debt gets the money left over once every minimum is paid.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Strategy {
+    /// Smallest balance first
+    Snowball,
+    /// Highest interest rate first
+    Avalanche,
+    /// Named debts first, in the order given, then the rest smallest first
+    Custom(Vec<String>),
+}
+
+impl Strategy {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Snowball => "Snowball",
+            Self::Avalanche => "Avalanche",
+            Self::Custom(_) => "Custom",
+        }
+    }
+
+    /// Indexes into `debts` in the order they are targeted.
+    pub fn order(&self, debts: &[Debt]) -> Result<Vec<usize>, PayoffErr> {
+        let mut by_balance: Vec<usize> = (0..debts.len()).collect();
+        by_balance.sort_by_key(|&i| (debts[i].balance, Reverse(debts[i].apr)));
+
+        match self {
+            Self::Snowball => Ok(by_balance),
+            Self::Avalanche => {
+                let mut by_rate: Vec<usize> = (0..debts.len()).collect();
+                by_rate.sort_by_key(|&i| (Reverse(debts[i].apr), debts[i].balance));
+                Ok(by_rate)
+            }
+            Self::Custom(names) => {
+                let mut order = Vec::new();
+                for name in names {
+                    let i = debts
+                        .iter()
+                        .position(|d| d.name.eq_ignore_ascii_case(name.trim()))
+                        .ok_or_else(|| DebtErr::NotFound(name.trim().to_string()))?;
+                    if !order.contains(&i) {
+                        order.push(i);
+                    }
+                }
+                let rest: Vec<usize> = by_balance
+                    .into_iter()
+                    .filter(|i| !order.contains(i))
+                    .collect();
+                order.extend(rest);
+                Ok(order)
+            }
+        }
+    }
+}
+
+/// One month of a payoff plan.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlanMonth {
+    pub month: YearMonth,
+    /// Paid towards each debt, in the same order as the debts
+    pub payments: Vec<Money>,
+    pub interest: Money,
+    /// Total still owed at the end of the month
+    pub remaining: Money,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PayoffPlan {
+    pub strategy: &'static str,
+    pub order: Vec<usize>,
+    pub months: Vec<PlanMonth>,
+    /// The month each debt is cleared, in the same order as the debts
+    pub paid_off: Vec<Option<YearMonth>>,
+}
+
+impl PayoffPlan {
+    /// The month the last debt is cleared.
+    pub fn debt_free(&self) -> Option<YearMonth> {
+        self.months.last().map(|m| m.month)
+    }
+
+    pub fn total_interest(&self) -> Money {
+        self.months.iter().map(|m| m.interest).sum()
+    }
+
+    pub fn total_paid(&self) -> Money {
+        self.months.iter().flat_map(|m| &m.payments).sum()
+    }
+}
+
+/// Pay the debts down month by month from `start`. Each month interest is
+/// added, every minimum is paid, and the rest of the budget (the minimums
+/// plus `extra`) goes to the debts in the strategy's order. The budget stays
+/// the same as debts are cleared, so a cleared debt's minimum rolls over to
+/// the next one.
+///
+/// The plan is abandoned as never paid off as soon as one debt's interest
+/// reaches the whole budget, or the total owed hasn't fallen over a year.
+pub fn simulate(
+    debts: &[Debt],
+    strategy: &Strategy,
+    extra: Money,
+    start: YearMonth,
+) -> Result<PayoffPlan, PayoffErr> {
+    if debts.is_empty() {
+        return Err(PayoffErr::NoDebts);
+    }
+    if extra.is_negative() {
+        return Err(PayoffErr::NegativeExtra);
+    }
+
+    let order = strategy.order(debts)?;
+    let never_paid_off = || PayoffErr::NeverPaidOff(strategy.name().to_lowercase());
+    let budget = total(debts.iter().map(|d| d.minimum).chain([extra]))?;
+    let mut balances: Vec<Money> = debts.iter().map(|d| d.balance).collect();
+    let mut owed = vec![total(balances.iter().copied())?];
+    let mut paid = Money::ZERO;
+    let mut paid_off = vec![None; debts.len()];
+    let mut months = Vec::new();
+
+    for offset in 0.. {
+        if balances.iter().all(|b| *b == Money::ZERO) {
+            break;
+        }
+        if offset == MAX_MONTHS {
+            return Err(never_paid_off());
+        }
+        let month = start.add_months(offset as i32);
+
+        let mut interest = Money::ZERO;
+        for (balance, debt) in balances.iter_mut().zip(debts) {
+            let charged = debt.apr.monthly_interest(*balance);
+            // Nothing left over for the other debts could ever bring it down
+            if charged >= budget {
+                return Err(never_paid_off());
+            }
+            *balance = balance.checked_add(charged).ok_or(PayoffErr::TooLarge)?;
+            interest = interest.checked_add(charged).ok_or(PayoffErr::TooLarge)?;
+        }
+
+        let mut payments: Vec<Money> = balances
+            .iter()
+            .zip(debts)
+            .map(|(balance, debt)| debt.minimum.min(*balance))
+            .collect();
+        let mut left = budget - payments.iter().sum::<Money>();
+        for &i in &order {
+            let extra = left.min(balances[i] - payments[i]);
+            payments[i] += extra;
+            left -= extra;
+        }
+
+        for (i, payment) in payments.iter().enumerate() {
+            if balances[i] > Money::ZERO && *payment == balances[i] {
+                paid_off[i] = Some(month);
+            }
+            balances[i] -= *payment;
+        }
+        // Keeps total_paid() and total_interest() from overflowing
+        paid = total(payments.iter().copied().chain([paid]))?;
+
+        let remaining = total(balances.iter().copied())?;
+        if owed.len() >= 12 && remaining >= owed[owed.len() - 12] {
+            return Err(never_paid_off());
+        }
+        owed.push(remaining);
+        months.push(PlanMonth {
+            month,
+            payments,
+            interest,
+            remaining,
+        });
+    }
+
+    Ok(PayoffPlan {
+        strategy: strategy.name(),
+        order,
+        months,
+        paid_off,
+    })
+}
+
+/// Sum `amounts`, failing rather than overflowing.
+fn total(mut amounts: impl Iterator<Item = Money>) -> Result<Money, PayoffErr> {
+    amounts.try_fold(Money::ZERO, |sum, m| {
+        sum.checked_add(m).ok_or(PayoffErr::TooLarge)
+    })
+}
+
+pub fn comparison_table(debts: &[Debt], plans: &[PayoffPlan]) -> Table {
+    let mut table = Table::new();
+
+    table.add_row(row![
+        "Strategy",
+        "Order (Paid Off)",
+        "Debt-Free",
+        "Months",
+        "Interest",
+        "Total Paid"
+    ]);
+
+    let least_interest = plans.iter().map(|p| p.total_interest()).min();
+    for plan in plans {
+        let order: Vec<String> = plan
+            .order
+            .iter()
+            .map(|&i| match plan.paid_off[i] {
+                Some(month) => format!("{} ({})", debts[i].name, month),
+                None => debts[i].name.clone(),
+            })
+            .collect();
+        let interest = format!("${}", plan.total_interest());
+        let interest_cell = if Some(plan.total_interest()) == least_interest {
+            Cell::new(&interest).style_spec("Fg")
+        } else {
+            Cell::new(&interest)
+        };
+        table.add_row(Row::new(vec![
+            Cell::new(plan.strategy),
+            Cell::new(&order.join("\n")),
+            Cell::new(&plan.debt_free().map(|m| m.to_string()).unwrap_or_default()),
+            Cell::new(&plan.months.len().to_string()),
+            interest_cell,
+            Cell::new(&format!("${}", plan.total_paid())),
+        ]));
+    }
+    table
+}
+
+pub fn schedule_table(debts: &[Debt], plan: &PayoffPlan) -> Table {
+    let mut table = Table::new();
+
+    let mut header = vec![Cell::new("Month")];
+    header.extend(plan.order.iter().map(|&i| Cell::new(&debts[i].name)));
+    header.push(Cell::new("Interest"));
+    header.push(Cell::new("Remaining"));
+    table.add_row(Row::new(header));
+
+    for month in &plan.months {
+        let mut cells = vec![Cell::new(&month.month.to_string())];
+        cells.extend(
+            plan.order
+                .iter()
+                .map(|&i| Cell::new(&format!("${}", month.payments[i]))),
+        );
+        cells.push(Cell::new(&format!("${}", month.interest)));
+        cells.push(Cell::new(&format!("${}", month.remaining)));
+        table.add_row(Row::new(cells));
+    }
+    table
+}
+
+pub fn print_plans(debts: &[Debt], plans: &[PayoffPlan]) {
+    let mut text = comparison_table(debts, plans).to_string();
+    for plan in plans {
+        text.push_str(&format!("\n{} schedule\n", plan.strategy));
+        text.push_str(&schedule_table(debts, plan).to_string());
+    }
+    crate::cli::display::page(&text);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{money, month, rate};
+
+    fn debts() -> Vec<Debt> {
+        vec![
+            Debt::new("Visa", money("3000"), rate("24"), money("90")).unwrap(),
+            Debt::new("Store card", money("500"), rate("12"), money("25")).unwrap(),
+            Debt::new("Car", money("8000"), rate("6"), money("250")).unwrap(),
+        ]
+    }
+
+    fn start() -> YearMonth {
+        month("2025-01")
+    }
+
+    #[test]
+    fn test_orders() {
+        let debts = debts();
+        assert_eq!(Strategy::Snowball.order(&debts).unwrap(), [1, 0, 2]);
+        assert_eq!(Strategy::Avalanche.order(&debts).unwrap(), [0, 1, 2]);
+
+        let custom = Strategy::Custom(vec!["car".to_string(), "CAR".to_string()]);
+        assert_eq!(custom.order(&debts).unwrap(), [2, 1, 0]);
+        let unknown = Strategy::Custom(vec!["Boat".to_string()]);
+        assert!(matches!(
+            unknown.order(&debts),
+            Err(PayoffErr::Debt(DebtErr::NotFound(_)))
+        ));
+    }
+
+    #[test]
+    fn test_single_debt_matches_hand_calculation() {
+        let debts = [Debt::new("Loan", money("1000"), rate("12"), money("300")).unwrap()];
+        let plan = simulate(&debts, &Strategy::Snowball, Money::ZERO, start()).unwrap();
+
+        // 1% a month: 1010 - 300 = 710, 717.10 - 300 = 417.10,
+        // 421.27 - 300 = 121.27, then 122.48 clears it
+        let paid: Vec<Money> = plan.months.iter().map(|m| m.payments[0]).collect();
+        assert_eq!(
+            paid,
+            [money("300"), money("300"), money("300"), money("122.48")]
+        );
+        assert_eq!(plan.total_interest(), money("22.48"));
+        assert_eq!(plan.total_paid(), money("1022.48"));
+        assert_eq!(plan.debt_free(), Some(month("2025-04")));
+        assert_eq!(plan.paid_off[0], Some(month("2025-04")));
+    }
+
+    #[test]
+    fn test_extra_goes_to_target_and_minimums_roll_over() {
+        let debts = debts();
+        let plan = simulate(&debts, &Strategy::Snowball, money("300"), start()).unwrap();
+
+        let first = &plan.months[0];
+        assert_eq!(first.payments[0], money("90"));
+        assert_eq!(first.payments[1], money("325"));
+        assert_eq!(first.payments[2], money("250"));
+        // The budget is spent in full until the final month
+        for month in &plan.months[..plan.months.len() - 1] {
+            assert_eq!(month.payments.iter().sum::<Money>(), money("665"));
+        }
+        assert_eq!(plan.months.last().unwrap().remaining, Money::ZERO);
+        assert!(plan.paid_off.iter().all(Option::is_some));
+        assert!(plan.paid_off[1] < plan.paid_off[0]);
+    }
+
+    #[test]
+    fn test_avalanche_pays_least_interest() {
+        let debts = debts();
+        let snowball = simulate(&debts, &Strategy::Snowball, money("300"), start()).unwrap();
+        let avalanche = simulate(&debts, &Strategy::Avalanche, money("300"), start()).unwrap();
+
+        assert!(avalanche.total_interest() <= snowball.total_interest());
+        assert_eq!(
+            avalanche.total_paid() - avalanche.total_interest(),
+            money("11500")
+        );
+    }
+
+    #[test]
+    fn test_never_paid_off() {
+        let debts = [Debt::new("Visa", money("10000"), rate("24"), money("50")).unwrap()];
+        assert!(matches!(
+            simulate(&debts, &Strategy::Avalanche, Money::ZERO, start()),
+            Err(PayoffErr::NeverPaidOff(_))
+        ));
+        assert!(matches!(
+            simulate(&[], &Strategy::Avalanche, Money::ZERO, start()),
+            Err(PayoffErr::NoDebts)
+        ));
+    }
+
+    #[test]
+    fn test_growing_balance_stops_early() {
+        // $150 of interest a month against a $50 payment
+        let debts = [Debt::new("Card", money("5000"), rate("36"), money("50")).unwrap()];
+        assert!(matches!(
+            simulate(&debts, &Strategy::Snowball, Money::ZERO, start()),
+            Err(PayoffErr::NeverPaidOff(_))
+        ));
+
+        // Each debt's interest is under the budget, but together they outgrow it
+        let debts = [
+            Debt::new("Visa", money("1000"), rate("36"), money("25")).unwrap(),
+            Debt::new("Amex", money("1000"), rate("36"), money("25")).unwrap(),
+        ];
+        assert!(matches!(
+            simulate(&debts, &Strategy::Avalanche, Money::ZERO, start()),
+            Err(PayoffErr::NeverPaidOff(_))
+        ));
+    }
+
+    #[test]
+    fn test_negative_extra_is_rejected() {
+        assert!(matches!(
+            simulate(&debts(), &Strategy::Snowball, money("-1"), start()),
+            Err(PayoffErr::NegativeExtra)
+        ));
+    }
+}
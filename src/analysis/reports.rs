@@ -0,0 +1,437 @@
+use crate::models::budget::{self, Budget};
+use crate::models::category::Category;
+use crate::models::money::Money;
+use crate::models::query::TransactionQuery;
+use crate::models::transaction::Transaction;
+use crate::utils::date_utils::YearMonth;
+use chrono::NaiveDate;
+use prettytable::{row, Cell, Row, Table};
+
+#[derive(Debug, thiserror::Error)]
+pub enum ReportErr {
+    #[error("The start of a report can't be after its end")]
+    RangeOrder,
+    #[error("A report can cover at most {0} months")]
+    RangeTooLong(i32),
+}
+
+/// Longest span the budget variance report covers.
+const MAX_VARIANCE_MONTHS: i32 = 120;
+
+/// One category's total over a report period.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CategoryTotal {
+    pub category: Category,
+    pub total: Money,
+}
+
+impl CategoryTotal {
+    pub fn category_type(&self) -> &'static str {
+        self.category.category_type()
+    }
+}
+
+/// Income, expenses and a per-category breakdown for one month or year.
+/// Transfers between accounts are left out.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Summary {
+    pub period: String,
+    pub income: Money,
+    pub expense: Money,
+    /// Sorted by amount, largest first
+    pub categories: Vec<CategoryTotal>,
+}
+
+impl Summary {
+    pub fn net(&self) -> Money {
+        self.income - self.expense
+    }
+
+    /// Share of income that wasn't spent, or `None` without income.
+    pub fn savings_rate(&self) -> Option<f64> {
+        self.net().percent_of(self.income)
+    }
+}
+
+fn summarize(
+    transactions: &[Transaction],
+    period: String,
+    from: NaiveDate,
+    to: NaiveDate,
+) -> Summary {
+    let mut categories: Vec<CategoryTotal> = Vec::new();
+
+    let query = TransactionQuery::between(from, to).without_transfers();
+    for t in query.apply(transactions) {
+        for (category, amount) in t.category_lines() {
+            match categories.iter_mut().find(|c| c.category.same(category)) {
+                Some(existing) => existing.total += amount,
+                None => categories.push(CategoryTotal {
+                    category: category.clone(),
+                    total: amount,
+                }),
+            }
+        }
+    }
+
+    let total_of = |kind: &str| {
+        categories
+            .iter()
+            .filter(|c| c.category_type() == kind)
+            .map(|c| c.total)
+            .sum()
+    };
+    let income = total_of("Income");
+    let expense = total_of("Expense");
+
+    categories.sort_by(|a, b| {
+        b.total
+            .cmp(&a.total)
+            .then_with(|| a.category.to_string().cmp(&b.category.to_string()))
+    });
+
+    Summary {
+        period,
+        income,
+        expense,
+        categories,
+    }
+}
+
+pub fn monthly(transactions: &[Transaction], month: YearMonth) -> Summary {
+    summarize(
+        transactions,
+        month.to_string(),
+        month.first_day(),
+        month.last_day(),
+    )
+}
+
+pub fn yearly(transactions: &[Transaction], year: i32) -> Summary {
+    let january = YearMonth { year, month: 1 };
+    summarize(
+        transactions,
+        year.to_string(),
+        january.first_day(),
+        january.add_months(11).last_day(),
+    )
+}
+
+/// Budgeted against actual spending for one category in one month.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VarianceRow {
+    pub month: YearMonth,
+    pub category: Category,
+    pub budgeted: Money,
+    pub actual: Money,
+}
+
+impl VarianceRow {
+    /// Money left under budget; negative when overspent.
+    pub fn variance(&self) -> Money {
+        self.budgeted - self.actual
+    }
+
+    pub fn variance_percent(&self) -> Option<f64> {
+        self.variance().percent_of(self.budgeted)
+    }
+}
+
+/// Every budget's variance for each month from `from` to `to`, month by
+/// month. Each month is measured against the limit in force that month, and
+/// months before a budget was first set are left out.
+pub fn budget_variance(
+    budgets: &[Budget],
+    transactions: &[Transaction],
+    from: YearMonth,
+    to: YearMonth,
+) -> Result<Vec<VarianceRow>, ReportErr> {
+    if from > to {
+        return Err(ReportErr::RangeOrder);
+    }
+    if from.months_until(to) >= MAX_VARIANCE_MONTHS {
+        return Err(ReportErr::RangeTooLong(MAX_VARIANCE_MONTHS));
+    }
+
+    let mut rows = Vec::new();
+    let mut month = from;
+    loop {
+        for status in budget::status(budgets, transactions, month) {
+            rows.push(VarianceRow {
+                month,
+                category: status.category,
+                budgeted: status.limit,
+                actual: status.spent,
+            });
+        }
+        if month == to {
+            return Ok(rows);
+        }
+        month = month.next();
+    }
+}
+
+/// A line for each budget set after `from`, saying which months the
+/// variance report leaves out.
+pub fn variance_notes(budgets: &[Budget], from: YearMonth) -> Vec<String> {
+    budgets
+        .iter()
+        .filter_map(|b| {
+            let first = b.first_month().filter(|m| *m > from)?;
+            Some(format!(
+                "{} has had a budget since {}; earlier months are left out",
+                b.category, first
+            ))
+        })
+        .collect()
+}
+
+/// The `count` most overspent rows, worst first.
+pub fn worst_offenders(rows: &[VarianceRow], count: usize) -> Vec<&VarianceRow> {
+    let mut over: Vec<&VarianceRow> = rows.iter().filter(|r| r.variance().is_negative()).collect();
+    over.sort_by_key(|r| r.variance());
+    over.truncate(count);
+    over
+}
+
+pub fn print_variance(rows: &[VarianceRow], worst: &[&VarianceRow]) {
+    let mut table = Table::new();
+
+    table.add_row(row![
+        "Month",
+        "Category",
+        "Budgeted",
+        "Actual",
+        "Variance",
+        "Variance %"
+    ]);
+
+    for r in rows {
+        let percent = r
+            .variance_percent()
+            .map(|p| format!("{:.1}%", p))
+            .unwrap_or_else(|| "-".to_string());
+        let style = if worst.contains(&r) {
+            "Fr"
+        } else if r.variance().is_negative() {
+            "Fy"
+        } else {
+            ""
+        };
+
+        table.add_row(Row::new(vec![
+            Cell::new(&r.month.to_string()),
+            Cell::new(&r.category.to_string()),
+            Cell::new(&format!("${}", r.budgeted)),
+            Cell::new(&format!("${}", r.actual)),
+            Cell::new(&format!("${}", r.variance())).style_spec(style),
+            Cell::new(&percent).style_spec(style),
+        ]));
+    }
+    table.printstd();
+
+    if worst.is_empty() {
+        println!("No category went over budget");
+        return;
+    }
+    println!("Worst offenders:");
+    for r in worst {
+        println!(
+            "  {} in {}: ${} over budget",
+            r.category,
+            r.month,
+            -r.variance()
+        );
+    }
+}
+
+pub fn print_summary(summary: &Summary) {
+    let mut totals = Table::new();
+
+    totals.add_row(row!["Income", "Expenses", "Net", "Savings Rate"]);
+
+    let net = format!("${}", summary.net());
+    let net_cell = if summary.net().is_negative() {
+        Cell::new(&net).style_spec("Fr")
+    } else {
+        Cell::new(&net)
+    };
+    totals.add_row(Row::new(vec![
+        Cell::new(&format!("${}", summary.income)),
+        Cell::new(&format!("${}", summary.expense)),
+        net_cell,
+        Cell::new(
+            &summary
+                .savings_rate()
+                .map(|r| format!("{:.1}%", r))
+                .unwrap_or_else(|| "-".to_string()),
+        ),
+    ]));
+
+    let mut breakdown = Table::new();
+
+    breakdown.add_row(row!["Category", "Type", "Amount", "Share"]);
+
+    for row in &summary.categories {
+        let kind_total = match row.category_type() {
+            "Income" => summary.income,
+            _ => summary.expense,
+        };
+        breakdown.add_row(Row::new(vec![
+            Cell::new(&row.category.to_string()),
+            Cell::new(row.category_type()),
+            Cell::new(&format!("${}", row.total)),
+            Cell::new(
+                &row.total
+                    .percent_of(kind_total)
+                    .map(|p| format!("{:.1}%", p))
+                    .unwrap_or_else(|| "-".to_string()),
+            ),
+        ]));
+    }
+
+    println!("Summary for {}", summary.period);
+    totals.printstd();
+    breakdown.printstd();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{day, ledger, money, month};
+
+    fn transactions() -> Vec<Transaction> {
+        let mut transactions = ledger(&[
+            ("03/01/2024", "3000", "Salary"),
+            ("03/02/2024", "120", "Food"),
+            ("03/05/2024", "900", "Bills"),
+            ("04/01/2024", "3000", "Salary"),
+            ("04/03/2024", "400", "expense:Pet Care"),
+            ("12/30/2023", "50", "Food"),
+        ]);
+        transactions.push(
+            Transaction::new_split(
+                7,
+                "03/09/2024",
+                "100",
+                &["Food=30".to_string(), "Shopping=70".to_string()],
+                None,
+            )
+            .unwrap(),
+        );
+        let (out_leg, in_leg) = Transaction::transfer_pair(
+            (8, 9),
+            day(2024, 3, 10),
+            money("500"),
+            ("Checking", "Savings"),
+            None,
+        )
+        .unwrap();
+        transactions.push(out_leg);
+        transactions.push(in_leg);
+        transactions
+    }
+
+    #[test]
+    fn test_monthly_totals_and_savings_rate() {
+        let summary = monthly(&transactions(), month("2024-03"));
+
+        assert_eq!(summary.income, money("3000"));
+        assert_eq!(summary.expense, money("1120"));
+        assert_eq!(summary.net(), money("1880"));
+        assert_eq!(summary.savings_rate(), Some(1880.0 / 3000.0 * 100.0));
+    }
+
+    #[test]
+    fn test_breakdown_sorted_and_excludes_transfers() {
+        let summary = monthly(&transactions(), month("2024-03"));
+        let names: Vec<String> = summary
+            .categories
+            .iter()
+            .map(|c| c.category.to_string())
+            .collect();
+
+        assert_eq!(names, ["Salary", "Bills", "Food", "Shopping"]);
+        assert_eq!(summary.categories[2].total, money("150"));
+    }
+
+    #[test]
+    fn test_yearly() {
+        let summary = yearly(&transactions(), 2024);
+
+        assert_eq!(summary.income, money("6000"));
+        assert_eq!(summary.expense, money("1520"));
+        assert_eq!(summary.categories[0].category_type(), "Income");
+    }
+
+    #[test]
+    fn test_budget_variance_per_month() {
+        let march = month("2024-03");
+        let mut budgets = Vec::new();
+        budget::set(&mut budgets, Category::Food, money("100"), march).unwrap();
+        budget::set(&mut budgets, Category::Bills, money("800"), march).unwrap();
+
+        let rows = budget_variance(
+            &budgets,
+            &transactions(),
+            month("2024-03"),
+            month("2024-04"),
+        )
+        .unwrap();
+
+        assert_eq!(rows.len(), 4);
+        assert_eq!(rows[0].actual, money("150"));
+        assert_eq!(rows[0].variance(), money("-50"));
+        assert_eq!(rows[0].variance_percent(), Some(-50.0));
+        assert_eq!(rows[2].month, month("2024-04"));
+        assert_eq!(rows[2].variance(), money("100"));
+
+        let worst = worst_offenders(&rows, 3);
+        assert_eq!(worst.len(), 2);
+        assert_eq!(worst[0].category, Category::Bills);
+        assert_eq!(worst[1].category, Category::Food);
+    }
+
+    #[test]
+    fn test_budget_variance_uses_limit_of_each_month() {
+        let (march, april) = (month("2024-03"), month("2024-04"));
+        let mut budgets = Vec::new();
+        budget::set(&mut budgets, Category::Food, money("100"), march).unwrap();
+        budget::set(&mut budgets, Category::Food, money("40"), april).unwrap();
+        budget::set(&mut budgets, Category::Bills, money("800"), april).unwrap();
+
+        let rows = budget_variance(
+            &budgets,
+            &transactions(),
+            month("2024-02"),
+            month("2024-04"),
+        )
+        .unwrap();
+
+        // nothing was budgeted in February, and Bills only from April
+        assert_eq!(rows.len(), 3);
+        assert_eq!(rows[0].month, month("2024-03"));
+        assert_eq!(rows[0].budgeted, money("100"));
+        assert_eq!(rows[1].budgeted, money("40"));
+        assert_eq!(rows[2].category, Category::Bills);
+
+        let notes = variance_notes(&budgets, month("2024-03"));
+        assert_eq!(notes.len(), 1);
+        assert!(notes[0].starts_with("Bills has had a budget since 2024-04"));
+    }
+
+    #[test]
+    fn test_budget_variance_rejects_bad_ranges() {
+        let result = budget_variance(&[], &[], month("2024-04"), month("2024-03"));
+        assert!(matches!(result, Err(ReportErr::RangeOrder)));
+        let result = budget_variance(&[], &[], month("2014-04"), month("2024-04"));
+        assert!(matches!(result, Err(ReportErr::RangeTooLong(120))));
+    }
+
+    #[test]
+    fn test_savings_rate_without_income() {
+        let summary = yearly(&transactions(), 2023);
+        assert_eq!(summary.net(), money("-50"));
+        assert_eq!(summary.savings_rate(), None);
+    }
+}
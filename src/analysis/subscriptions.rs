@@ -0,0 +1,362 @@
+use crate::models::category::Category;
+use crate::models::money::Money;
+use crate::models::query::TransactionQuery;
+use crate::models::transaction::Transaction;
+use crate::utils::date_utils;
+use chrono::{Datelike, Duration, NaiveDate};
+use prettytable::{row, Cell, Row, Table};
+use std::fmt;
+
+/// Fewest charges before a repeating payment counts as a subscription.
+const MIN_CHARGES: usize = 3;
+/// How far, as a percentage, a charge may be from the typical amount.
+const AMOUNT_TOLERANCE: f64 = 25.0;
+
+/// How often a subscription charges.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Cadence {
+    Weekly,
+    Biweekly,
+    Monthly,
+    Quarterly,
+    Yearly,
+}
+
+impl fmt::Display for Cadence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Weekly => write!(f, "Weekly"),
+            Self::Biweekly => write!(f, "Biweekly"),
+            Self::Monthly => write!(f, "Monthly"),
+            Self::Quarterly => write!(f, "Quarterly"),
+            Self::Yearly => write!(f, "Yearly"),
+        }
+    }
+}
+
+impl Cadence {
+    /// The cadence a gap of `days` between charges fits, allowing for
+    /// months of different lengths and charges landing a day or two late.
+    pub fn from_interval(days: i64) -> Option<Self> {
+        match days {
+            6..=8 => Some(Self::Weekly),
+            13..=16 => Some(Self::Biweekly),
+            27..=33 => Some(Self::Monthly),
+            85..=96 => Some(Self::Quarterly),
+            355..=375 => Some(Self::Yearly),
+            _ => None,
+        }
+    }
+
+    pub fn per_year(self) -> i64 {
+        match self {
+            Self::Weekly => 52,
+            Self::Biweekly => 26,
+            Self::Monthly => 12,
+            Self::Quarterly => 4,
+            Self::Yearly => 1,
+        }
+    }
+
+    /// The charge expected one period after `date`.
+    pub fn after(self, date: NaiveDate) -> NaiveDate {
+        let months = match self {
+            Self::Weekly => return date + Duration::days(7),
+            Self::Biweekly => return date + Duration::days(14),
+            Self::Monthly => 1,
+            Self::Quarterly => 3,
+            Self::Yearly => 12,
+        };
+        date_utils::shift_months(date, months, date.day()).unwrap_or(date)
+    }
+
+    /// Days past the expected date before a charge counts as missed.
+    fn grace_days(self) -> i64 {
+        match self {
+            Self::Weekly => 4,
+            Self::Biweekly => 7,
+            Self::Monthly => 10,
+            Self::Quarterly => 20,
+            Self::Yearly => 30,
+        }
+    }
+}
+
+/// A payment that looks like a subscription.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Subscription {
+    pub name: String,
+    pub category: Category,
+    pub cadence: Cadence,
+    pub charges: usize,
+    pub amount: Money,
+    pub previous_amount: Money,
+    pub last_charge: NaiveDate,
+}
+
+impl Subscription {
+    pub fn next_expected(&self) -> NaiveDate {
+        self.cadence.after(self.last_charge)
+    }
+
+    pub fn annual_cost(&self) -> Money {
+        self.amount.mul_ratio(self.cadence.per_year(), 1)
+    }
+
+    /// How much the latest charge went up by, if it did.
+    pub fn price_increase(&self) -> Option<Money> {
+        let increase = self.amount - self.previous_amount;
+        (increase > Money::ZERO).then_some(increase)
+    }
+
+    /// The next charge is overdue by more than a grace period.
+    pub fn is_stopped(&self, today: NaiveDate) -> bool {
+        today > self.next_expected() + Duration::days(self.cadence.grace_days())
+    }
+}
+
+/// Lower-case the words of a note, dropping digits and punctuation so
+/// "NETFLIX.COM 03/24" and "Netflix.com" group together.
+fn normalize(note: &str) -> String {
+    note.split(|c: char| !c.is_alphabetic())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn median(amounts: &mut [Money]) -> Money {
+    amounts.sort();
+    amounts[amounts.len() / 2]
+}
+
+/// Find expenses that repeat at a steady interval with a similar amount
+/// and note. Transactions without a note can't be told apart and are
+/// skipped. Only the charges before the latest need similar amounts, so a
+/// price change in the latest one still shows up, as a price increase.
+pub fn detect(transactions: &[Transaction]) -> Vec<Subscription> {
+    let mut groups: Vec<(String, &Category, Vec<&Transaction>)> = Vec::new();
+    for t in TransactionQuery::default()
+        .with_kind("Expense")
+        .apply(transactions)
+    {
+        // `add` stores "N/A" when no note is given
+        let key = normalize(t.note().unwrap_or_default());
+        if key.is_empty() || key == "n a" {
+            continue;
+        }
+        match groups
+            .iter_mut()
+            .find(|(k, c, _)| *k == key && c.same(t.category()))
+        {
+            Some((_, _, charges)) => charges.push(t),
+            None => groups.push((key, t.category(), vec![t])),
+        }
+    }
+
+    let mut found = Vec::new();
+    for (_, category, mut charges) in groups {
+        if charges.len() < MIN_CHARGES {
+            continue;
+        }
+        charges.sort_by_key(|t| (t.date(), t.id()));
+
+        let mut gaps: Vec<i64> = charges
+            .windows(2)
+            .map(|pair| (pair[1].date() - pair[0].date()).num_days())
+            .collect();
+        gaps.sort();
+        let Some(cadence) = Cadence::from_interval(gaps[gaps.len() / 2]) else {
+            continue;
+        };
+        if gaps
+            .iter()
+            .any(|&gap| Cadence::from_interval(gap) != Some(cadence))
+        {
+            continue;
+        }
+
+        let Some((last, earlier)) = charges.split_last() else {
+            continue;
+        };
+        let typical = median(&mut earlier.iter().map(|t| t.amount()).collect::<Vec<_>>());
+        let similar = earlier.iter().all(|t| {
+            (t.amount() - typical)
+                .abs()
+                .percent_of(typical)
+                .is_some_and(|p| p <= AMOUNT_TOLERANCE)
+        });
+        if !similar {
+            continue;
+        }
+
+        let previous = earlier[earlier.len() - 1];
+        found.push(Subscription {
+            name: last.note().unwrap_or_default().to_string(),
+            category: category.clone(),
+            cadence,
+            charges: charges.len(),
+            amount: last.amount(),
+            previous_amount: previous.amount(),
+            last_charge: last.date(),
+        });
+    }
+
+    found.sort_by_key(|s| std::cmp::Reverse(s.annual_cost()));
+    found
+}
+
+pub fn print_subscriptions(subscriptions: &[Subscription], today: NaiveDate) {
+    let mut table = Table::new();
+
+    table.add_row(row![
+        "Name",
+        "Category",
+        "Cadence",
+        "Amount",
+        "Last Charge",
+        "Next Expected",
+        "Per Year",
+        "Flags"
+    ]);
+
+    for sub in subscriptions {
+        let mut flags = Vec::new();
+        if let Some(increase) = sub.price_increase() {
+            flags.push(format!("Price up ${}", increase));
+        }
+        if sub.is_stopped(today) {
+            flags.push("Stopped?".to_string());
+        }
+        let flags_cell = if flags.is_empty() {
+            Cell::new("")
+        } else {
+            Cell::new(&flags.join(", ")).style_spec("Fr")
+        };
+
+        table.add_row(Row::new(vec![
+            Cell::new(&sub.name),
+            Cell::new(&sub.category.to_string()),
+            Cell::new(&sub.cadence.to_string()),
+            Cell::new(&format!("${}", sub.amount)),
+            Cell::new(&sub.last_charge.to_string()),
+            Cell::new(&sub.next_expected().to_string()),
+            Cell::new(&format!("${}", sub.annual_cost())),
+            flags_cell,
+        ]));
+    }
+    table.printstd();
+
+    let active: Money = subscriptions
+        .iter()
+        .filter(|s| !s.is_stopped(today))
+        .map(|s| s.annual_cost())
+        .sum();
+    println!("Active subscriptions cost ${} a year", active);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{day, money};
+
+    fn charge(id: u32, date: &str, amount: &str, note: &str) -> Transaction {
+        Transaction::new(id, date, amount, "Entertainment", Some(note.to_string())).unwrap()
+    }
+
+    #[test]
+    fn test_cadence_from_interval() {
+        assert_eq!(Cadence::from_interval(7), Some(Cadence::Weekly));
+        assert_eq!(Cadence::from_interval(29), Some(Cadence::Monthly));
+        assert_eq!(Cadence::from_interval(31), Some(Cadence::Monthly));
+        assert_eq!(Cadence::from_interval(366), Some(Cadence::Yearly));
+        assert_eq!(Cadence::from_interval(45), None);
+    }
+
+    #[test]
+    fn test_cadence_after_clamps_month_end() {
+        assert_eq!(Cadence::Monthly.after(day(2024, 1, 31)), day(2024, 2, 29));
+        assert_eq!(Cadence::Biweekly.after(day(2024, 1, 31)), day(2024, 2, 14));
+    }
+
+    #[test]
+    fn test_detect_monthly_with_price_increase() {
+        let transactions = [
+            charge(1, "01/05/2024", "15.49", "NETFLIX.COM 01/24"),
+            charge(2, "02/05/2024", "15.49", "Netflix.com 02/24"),
+            charge(3, "03/06/2024", "17.99", "netflix.com"),
+            charge(4, "03/09/2024", "40", "Cinema"),
+        ];
+
+        let found = detect(&transactions);
+
+        assert_eq!(found.len(), 1);
+        let netflix = &found[0];
+        assert_eq!(netflix.cadence, Cadence::Monthly);
+        assert_eq!(netflix.charges, 3);
+        assert_eq!(netflix.next_expected(), day(2024, 4, 6));
+        assert_eq!(netflix.annual_cost(), money("215.88"));
+        assert_eq!(netflix.price_increase(), Some(money("2.50")));
+    }
+
+    #[test]
+    fn test_detect_large_price_increase_in_latest_charge() {
+        let transactions = [
+            charge(1, "01/10/2024", "10", "Music"),
+            charge(2, "02/10/2024", "10", "Music"),
+            charge(3, "03/10/2024", "10", "Music"),
+            charge(4, "04/10/2024", "15", "Music"),
+        ];
+
+        let found = detect(&transactions);
+
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].charges, 4);
+        assert_eq!(found[0].amount, money("15"));
+        assert_eq!(found[0].price_increase(), Some(money("5")));
+    }
+
+    #[test]
+    fn test_detect_needs_steady_interval_and_amount() {
+        let irregular = [
+            charge(1, "01/05/2024", "20", "Gym"),
+            charge(2, "02/05/2024", "20", "Gym"),
+            charge(3, "04/20/2024", "20", "Gym"),
+        ];
+        assert!(detect(&irregular).is_empty());
+
+        let varying = [
+            charge(1, "01/05/2024", "20", "Gym"),
+            charge(2, "02/05/2024", "60", "Gym"),
+            charge(3, "03/05/2024", "20", "Gym"),
+        ];
+        assert!(detect(&varying).is_empty());
+    }
+
+    #[test]
+    fn test_detect_skips_income_and_missing_notes() {
+        let transactions = [
+            Transaction::new(1, "01/01/2024", "3000", "Salary", Some("Acme".to_string())).unwrap(),
+            Transaction::new(2, "02/01/2024", "3000", "Salary", Some("Acme".to_string())).unwrap(),
+            Transaction::new(3, "03/01/2024", "3000", "Salary", Some("Acme".to_string())).unwrap(),
+            charge(4, "01/05/2024", "10", "N/A"),
+            charge(5, "02/05/2024", "10", "N/A"),
+            charge(6, "03/05/2024", "10", "N/A"),
+        ];
+        assert!(detect(&transactions).is_empty());
+    }
+
+    #[test]
+    fn test_stopped_subscription() {
+        let transactions = [
+            charge(1, "01/10/2024", "9.99", "Spotify"),
+            charge(2, "02/10/2024", "9.99", "Spotify"),
+            charge(3, "03/10/2024", "9.99", "Spotify"),
+        ];
+        let spotify = &detect(&transactions)[0];
+
+        assert!(!spotify.is_stopped(day(2024, 4, 15)));
+        assert!(spotify.is_stopped(day(2024, 5, 1)));
+        assert_eq!(spotify.price_increase(), None);
+    }
+}
@@ -0,0 +1,550 @@
+use crate::models::category::Category;
+use crate::models::money::Money;
+use crate::models::query::TransactionQuery;
+use crate::models::transaction::Transaction;
+use crate::utils::date_utils::YearMonth;
+use prettytable::{row, Cell, Row, Table};
+
+/// How one expense category's spending has moved up to a given month.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CategoryTrend {
+    pub category: Category,
+    /// Monthly totals, oldest first, ending with the reported month
+    pub history: Vec<Money>,
+    /// Spending rose every month over the requested window
+    pub rising: bool,
+}
+
+impl CategoryTrend {
+    pub fn current(&self) -> Money {
+        self.history.last().copied().unwrap_or(Money::ZERO)
+    }
+
+    fn months_back(&self, months: usize) -> Money {
+        self.history
+            .len()
+            .checked_sub(months + 1)
+            .map(|i| self.history[i])
+            .unwrap_or(Money::ZERO)
+    }
+
+    /// Percent change from the previous month, or `None` when nothing was
+    /// spent then.
+    pub fn month_over_month(&self) -> Option<f64> {
+        percent_change(self.months_back(1), self.current())
+    }
+
+    /// Percent change from the same month a year earlier.
+    pub fn year_over_year(&self) -> Option<f64> {
+        percent_change(self.months_back(12), self.current())
+    }
+
+    pub fn average(&self, window: usize) -> Money {
+        moving_average(&self.history, window)
+    }
+}
+
+fn percent_change(before: Money, after: Money) -> Option<f64> {
+    (after - before).percent_of(before)
+}
+
+/// Average of the last `window` values; missing months count as zero.
+pub fn moving_average(values: &[Money], window: usize) -> Money {
+    if window == 0 {
+        return Money::ZERO;
+    }
+    let start = values.len().saturating_sub(window);
+    let total: Money = values[start..].iter().sum();
+    total.mul_ratio(1, window as i64)
+}
+
+/// True when every value is higher than the one before it.
+pub fn is_rising(values: &[Money]) -> bool {
+    values.len() >= 2 && values.windows(2).all(|pair| pair[1] > pair[0])
+}
+
+/// Total spent in `category` in each of `months`.
+pub fn monthly_totals(
+    transactions: &[Transaction],
+    category: &Category,
+    months: &[YearMonth],
+) -> Vec<Money> {
+    months
+        .iter()
+        .map(|&month| {
+            TransactionQuery::in_month(month)
+                .with_category(category)
+                .apply(transactions)
+                .into_iter()
+                .flat_map(|t| t.category_lines())
+                .filter(|(c, _)| c.same(category))
+                .map(|(_, amount)| amount)
+                .sum()
+        })
+        .collect()
+}
+
+/// Trends for every expense category with spending in the year up to
+/// `month`. A category is rising when its total grew every month over the
+/// last `window` months.
+pub fn category_trends(
+    transactions: &[Transaction],
+    month: YearMonth,
+    window: usize,
+) -> Vec<CategoryTrend> {
+    // the reported month, the 12 before it for year-over-year, and enough
+    // to cover a longer window
+    let span = window.max(13) as i32;
+    let months: Vec<YearMonth> = (1 - span..=0).map(|i| month.add_months(i)).collect();
+
+    let spending = TransactionQuery::between(months[0].first_day(), month.last_day())
+        .with_kind("Expense")
+        .apply(transactions);
+    let mut categories: Vec<&Category> = Vec::new();
+    for t in spending {
+        for (c, _) in t.category_lines() {
+            if !categories.iter().any(|known| known.same(c)) {
+                categories.push(c);
+            }
+        }
+    }
+
+    let mut trends: Vec<CategoryTrend> = categories
+        .into_iter()
+        .map(|category| {
+            let history = monthly_totals(transactions, category, &months);
+            let recent = &history[history.len().saturating_sub(window)..];
+            CategoryTrend {
+                category: category.clone(),
+                rising: is_rising(recent),
+                history,
+            }
+        })
+        .collect();
+
+    trends.sort_by(|a, b| {
+        b.rising
+            .cmp(&a.rising)
+            .then_with(|| b.current().cmp(&a.current()))
+    });
+    trends
+}
+
+fn format_change(change: Option<f64>) -> String {
+    change
+        .map(|c| format!("{:+.1}%", c))
+        .unwrap_or_else(|| "-".to_string())
+}
+
+pub fn print_trends(trends: &[CategoryTrend], month: YearMonth) {
+    let mut table = Table::new();
+
+    table.add_row(row![
+        "Category",
+        "Spent",
+        "MoM",
+        "YoY",
+        "3-Mo Avg",
+        "12-Mo Avg",
+        "Trend"
+    ]);
+
+    for trend in trends {
+        let flag = if trend.rising {
+            Cell::new("Rising").style_spec("Fr")
+        } else {
+            Cell::new("")
+        };
+
+        table.add_row(Row::new(vec![
+            Cell::new(&trend.category.to_string()),
+            Cell::new(&format!("${}", trend.current())),
+            Cell::new(&format_change(trend.month_over_month())),
+            Cell::new(&format_change(trend.year_over_year())),
+            Cell::new(&format!("${}", trend.average(3))),
+            Cell::new(&format!("${}", trend.average(12))),
+            flag,
+        ]));
+    }
+
+    println!("Spending trends for {}", month);
+    table.printstd();
+}
+
+/// Robust z-score above which an amount counts as unusual.
+const OUTLIER_SCORE: f64 = 3.5;
+/// Fewest non-zero past amounts needed before anything is flagged.
+const MIN_HISTORY: usize = 5;
+
+fn median(values: &[Money]) -> Money {
+    let mut sorted = values.to_vec();
+    sorted.sort();
+    let mid = sorted.len() / 2;
+    if sorted.is_empty() {
+        Money::ZERO
+    } else if sorted.len().is_multiple_of(2) {
+        (sorted[mid - 1] + sorted[mid]).mul_ratio(1, 2)
+    } else {
+        sorted[mid]
+    }
+}
+
+/// An amount far from the typical value of its history.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Outlier {
+    pub median: Money,
+    /// Robust z-score: distance from the median in units of the median
+    /// absolute deviation, scaled to be comparable to a standard score
+    pub score: f64,
+}
+
+/// Check `value` against `history` using the median absolute deviation.
+///
+/// When every past amount is the same the deviation is zero; then a value
+/// is only flagged when it is further from the median than the median
+/// itself. A median of zero, as for a category with nothing spent in most
+/// months, flags nothing.
+pub fn outlier(value: Money, history: &[Money]) -> Option<Outlier> {
+    if history.iter().filter(|&&v| v != Money::ZERO).count() < MIN_HISTORY {
+        return None;
+    }
+    let typical = median(history);
+    let deviations: Vec<Money> = history.iter().map(|&v| (v - typical).abs()).collect();
+    let mad = median(&deviations);
+    let distance = (value - typical).abs();
+
+    let score = match distance.percent_of(mad) {
+        Some(ratio) => 0.6745 * ratio / 100.0,
+        None if typical != Money::ZERO && distance > typical.abs() => f64::INFINITY,
+        None => 0.0,
+    };
+    if score > OUTLIER_SCORE {
+        Some(Outlier {
+            median: typical,
+            score,
+        })
+    } else {
+        None
+    }
+}
+
+/// A transaction whose amount is unusual for its category.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TransactionAnomaly {
+    pub id: u32,
+    pub category: Category,
+    pub amount: Money,
+    pub outlier: Outlier,
+}
+
+/// A month whose total is unusual for its category.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MonthAnomaly {
+    pub month: YearMonth,
+    pub category: Category,
+    pub total: Money,
+    pub outlier: Outlier,
+}
+
+/// Every category amount of the transactions matching `query`, grouped
+/// by category.
+fn amounts_by_category(
+    transactions: &[Transaction],
+    query: &TransactionQuery,
+) -> Vec<(Category, Vec<(u32, Money)>)> {
+    let mut groups: Vec<(Category, Vec<(u32, Money)>)> = Vec::new();
+    for t in query.apply(transactions) {
+        for (category, amount) in t.category_lines() {
+            match groups.iter_mut().find(|(c, _)| c.same(category)) {
+                Some((_, amounts)) => amounts.push((t.id(), amount)),
+                None => groups.push((category.clone(), vec![(t.id(), amount)])),
+            }
+        }
+    }
+    groups
+}
+
+/// Transactions whose amount is far outside the usual range for their
+/// category.
+pub fn transaction_anomalies(transactions: &[Transaction]) -> Vec<TransactionAnomaly> {
+    let mut anomalies = Vec::new();
+    let query = TransactionQuery::default().without_transfers();
+    for (category, amounts) in amounts_by_category(transactions, &query) {
+        let history: Vec<Money> = amounts.iter().map(|&(_, amount)| amount).collect();
+        for &(id, amount) in &amounts {
+            if let Some(outlier) = outlier(amount, &history) {
+                anomalies.push(TransactionAnomaly {
+                    id,
+                    category: category.clone(),
+                    amount,
+                    outlier,
+                });
+            }
+        }
+    }
+    anomalies.sort_by_key(|a| a.id);
+    anomalies
+}
+
+/// Check a transaction that is about to be added against the ledger. Only
+/// the first unusual category line is reported. Payments from a sinking
+/// fund are expected to be large and are not checked.
+pub fn check_new(transactions: &[Transaction], new: &Transaction) -> Option<TransactionAnomaly> {
+    if new.fund().is_some() {
+        return None;
+    }
+    let groups = amounts_by_category(
+        transactions,
+        &TransactionQuery::default().without_transfers(),
+    );
+    new.category_lines()
+        .into_iter()
+        .find_map(|(category, amount)| {
+            let (_, amounts) = groups.iter().find(|(c, _)| c.same(category))?;
+            let history: Vec<Money> = amounts.iter().map(|&(_, amount)| amount).collect();
+            Some(TransactionAnomaly {
+                id: new.id(),
+                category: category.clone(),
+                amount,
+                outlier: outlier(amount, &history)?,
+            })
+        })
+}
+
+/// Expense categories whose total in a whole month up to `through` stands
+/// out from their other months.
+pub fn month_anomalies(transactions: &[Transaction], through: YearMonth) -> Vec<MonthAnomaly> {
+    let Some(first) = transactions.iter().map(|t| YearMonth::of(t.date())).min() else {
+        return Vec::new();
+    };
+    if first > through {
+        return Vec::new();
+    }
+    let months: Vec<YearMonth> = (0..=first.months_until(through))
+        .map(|i| first.add_months(i))
+        .collect();
+
+    let mut anomalies = Vec::new();
+    let spending = TransactionQuery::default().with_kind("Expense");
+    for (category, _) in amounts_by_category(transactions, &spending) {
+        let totals = monthly_totals(transactions, &category, &months);
+        // a category's history starts with its first spending
+        let Some(start) = totals.iter().position(|&t| t != Money::ZERO) else {
+            continue;
+        };
+        for (i, &total) in totals.iter().enumerate().skip(start) {
+            let others: Vec<Money> = totals
+                .iter()
+                .enumerate()
+                .skip(start)
+                .filter(|&(j, _)| j != i)
+                .map(|(_, &t)| t)
+                .collect();
+            if let Some(outlier) = outlier(total, &others) {
+                anomalies.push(MonthAnomaly {
+                    month: months[i],
+                    category: category.clone(),
+                    total,
+                    outlier,
+                });
+            }
+        }
+    }
+    anomalies.sort_by_key(|a| a.month);
+    anomalies
+}
+
+impl TransactionAnomaly {
+    pub fn warning(&self) -> String {
+        format!(
+            "${} is unusual for {} (typically ${}); check the amount or use --force to add it anyway",
+            self.amount, self.category, self.outlier.median
+        )
+    }
+}
+
+pub fn print_anomalies(transactions: &[TransactionAnomaly], months: &[MonthAnomaly]) {
+    if transactions.is_empty() {
+        println!("No unusual transactions");
+    } else {
+        let mut table = Table::new();
+
+        table.add_row(row!["ID", "Category", "Amount", "Typical", "Score"]);
+
+        for a in transactions {
+            table.add_row(Row::new(vec![
+                Cell::new(&a.id.to_string()),
+                Cell::new(&a.category.to_string()),
+                Cell::new(&format!("${}", a.amount)).style_spec("Fr"),
+                Cell::new(&format!("${}", a.outlier.median)),
+                Cell::new(&format_score(a.outlier.score)),
+            ]));
+        }
+        println!("Unusual transactions");
+        table.printstd();
+    }
+
+    if months.is_empty() {
+        println!("No unusual monthly totals");
+    } else {
+        let mut table = Table::new();
+
+        table.add_row(row!["Month", "Category", "Total", "Typical", "Score"]);
+
+        for a in months {
+            table.add_row(Row::new(vec![
+                Cell::new(&a.month.to_string()),
+                Cell::new(&a.category.to_string()),
+                Cell::new(&format!("${}", a.total)).style_spec("Fr"),
+                Cell::new(&format!("${}", a.outlier.median)),
+                Cell::new(&format_score(a.outlier.score)),
+            ]));
+        }
+        println!("Unusual monthly totals");
+        table.printstd();
+    }
+}
+
+fn format_score(score: f64) -> String {
+    if score.is_finite() {
+        format!("{:.1}", score)
+    } else {
+        "-".to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{money, month, transaction};
+
+    #[test]
+    fn test_moving_average() {
+        let values = [money("10"), money("20"), money("30"), money("40")];
+        assert_eq!(moving_average(&values, 3), money("30"));
+        // missing months count as zero
+        assert_eq!(moving_average(&values, 12), money("8.33"));
+        assert_eq!(moving_average(&values, 0), Money::ZERO);
+    }
+
+    #[test]
+    fn test_is_rising() {
+        assert!(is_rising(&[money("1"), money("2"), money("3")]));
+        assert!(!is_rising(&[money("1"), money("3"), money("3")]));
+        assert!(!is_rising(&[money("5")]));
+    }
+
+    #[test]
+    fn test_category_trends() {
+        let transactions = [
+            transaction(1, "03/10/2023", "50", "Food"),
+            transaction(2, "01/10/2024", "100", "Food"),
+            transaction(3, "02/10/2024", "120", "Food"),
+            transaction(4, "03/10/2024", "150", "Food"),
+            transaction(5, "02/10/2024", "80", "Shopping"),
+            transaction(6, "03/10/2024", "40", "Shopping"),
+            transaction(7, "03/01/2024", "3000", "Salary"),
+        ];
+
+        let trends = category_trends(&transactions, month("2024-03"), 3);
+
+        assert_eq!(trends.len(), 2);
+        let food = &trends[0];
+        assert_eq!(food.category, Category::Food);
+        assert!(food.rising);
+        assert_eq!(food.current(), money("150"));
+        assert_eq!(food.month_over_month(), Some(25.0));
+        assert_eq!(food.year_over_year(), Some(200.0));
+        assert_eq!(food.average(3), money("123.33"));
+
+        let shopping = &trends[1];
+        assert!(!shopping.rising);
+        assert_eq!(shopping.month_over_month(), Some(-50.0));
+        assert_eq!(shopping.year_over_year(), None);
+    }
+
+    fn amounts(values: &[&str]) -> Vec<Money> {
+        values.iter().map(|v| money(v)).collect()
+    }
+
+    #[test]
+    fn test_outlier_uses_median_absolute_deviation() {
+        let history = amounts(&["14", "15", "15.50", "16", "17", "15"]);
+
+        let typo = outlier(money("1500"), &history).unwrap();
+        assert_eq!(typo.median, money("15.25"));
+        assert!(outlier(money("17.50"), &history).is_none());
+        assert!(outlier(money("1.50"), &history).is_some());
+    }
+
+    #[test]
+    fn test_outlier_needs_history() {
+        let history = amounts(&["15", "15", "15", "15"]);
+        assert!(outlier(money("1500"), &history).is_none());
+    }
+
+    #[test]
+    fn test_outlier_needs_non_zero_history() {
+        let history = amounts(&["0", "0", "0", "0", "0", "0", "20", "0"]);
+        assert!(outlier(money("50"), &history).is_none());
+    }
+
+    #[test]
+    fn test_outlier_with_identical_history() {
+        let history = amounts(&["9.99", "9.99", "9.99", "9.99", "9.99"]);
+        assert!(outlier(money("10.99"), &history).is_none());
+        assert!(outlier(money("99.90"), &history).is_some());
+    }
+
+    #[test]
+    fn test_transaction_anomalies_and_check_new() {
+        let mut transactions: Vec<Transaction> = ["12", "15", "14", "16", "13", "15"]
+            .iter()
+            .enumerate()
+            .map(|(i, amount)| transaction(i as u32 + 1, "03/10/2024", amount, "Food"))
+            .collect();
+        transactions.push(transaction(7, "03/11/2024", "1400", "Food"));
+
+        let found = transaction_anomalies(&transactions);
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].id, 7);
+
+        let typo = transaction(0, "03/12/2024", "1500", "Food");
+        assert!(check_new(&transactions, &typo).is_some());
+        let normal = transaction(0, "03/12/2024", "15", "Food");
+        assert!(check_new(&transactions, &normal).is_none());
+        let new_category = transaction(0, "03/12/2024", "1500", "Bills");
+        assert!(check_new(&transactions, &new_category).is_none());
+        let from_fund = typo.with_fund(Some("Holidays".to_string()));
+        assert!(check_new(&transactions, &from_fund).is_none());
+    }
+
+    #[test]
+    fn test_month_anomalies() {
+        let mut transactions: Vec<Transaction> = (1..=8)
+            .map(|m| transaction(m, &format!("{:02}/05/2024", m), "100", "Bills"))
+            .collect();
+        transactions.push(transaction(9, "05/20/2024", "900", "Bills"));
+
+        let found = month_anomalies(&transactions, month("2024-08"));
+
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].month, month("2024-05"));
+        assert_eq!(found[0].total, money("1000"));
+    }
+
+    #[test]
+    fn test_month_anomalies_ignore_new_and_occasional_categories() {
+        let mut transactions: Vec<Transaction> = (1..=8)
+            .map(|m| transaction(m, &format!("{:02}/05/2024", m), "100", "Bills"))
+            .collect();
+        // first spent in July, then only now and again
+        for (id, date) in [(9, "07/10/2024"), (10, "08/10/2024")] {
+            transactions.push(transaction(id, date, "40", "Entertainment"));
+        }
+        transactions.push(transaction(11, "03/15/2024", "60", "Healthcare"));
+        transactions.push(transaction(12, "08/15/2024", "60", "Healthcare"));
+
+        assert!(month_anomalies(&transactions, month("2024-08")).is_empty());
+    }
+}
@@ -0,0 +1,38 @@
+use std::io::{IsTerminal, Write};
+use std::process::{Command, Stdio};
+
+/// Print `text`, going through `$PAGER` (or `less`) when stdout is a
+/// terminal. Falls back to plain printing if the pager can't be started.
+pub fn page(text: &str) {
+    if !std::io::stdout().is_terminal() {
+        print!("{}", text);
+        return;
+    }
+
+    let pager = std::env::var("PAGER").unwrap_or_else(|_| "less -FRX".to_string());
+    let mut parts = pager.split_whitespace();
+    let spawned = match parts.next() {
+        Some(program) => Command::new(program)
+            .args(parts)
+            .stdin(Stdio::piped())
+            .spawn(),
+        None => {
+            print!("{}", text);
+            return;
+        }
+    };
+
+    let mut child = match spawned {
+        Ok(child) => child,
+        Err(_) => {
+            print!("{}", text);
+            return;
+        }
+    };
+
+    if let Some(mut stdin) = child.stdin.take() {
+        // the user quitting the pager early closes the pipe; that's fine
+        let _ = stdin.write_all(text.as_bytes());
+    }
+    let _ = child.wait();
+}
@@ -2,9 +2,12 @@ mod analysis;
 mod cli;
 mod models;
 mod storage;
+#[cfg(test)]
+mod test_util;
 mod utils;
 
 use crate::cli::FinCli;
+use crate::storage::migration;
 use crate::utils::validation;
 use clap::Parser;
 
@@ -12,6 +15,18 @@ fn main() {
     // on init check if there is a data file, if not create one.
     validation::data_file_exists();
 
+    match migration::run() {
+        Ok(report) => {
+            for line in report {
+                println!("{}", line);
+            }
+        }
+        Err(e) => {
+            eprintln!("Could not migrate data files: {}", e);
+            std::process::exit(1);
+        }
+    }
+
     let cli = FinCli::parse();
     cli.command.process();
 }
@@ -0,0 +1,365 @@
+use super::money::Money;
+use super::transaction::Transaction;
+use chrono::NaiveDate;
+use prettytable::{row, Cell, Row, Table};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AccountKind {
+    Checking,
+    Savings,
+    Credit,
+    Cash,
+    Loan,
+}
+
+impl fmt::Display for AccountKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Checking => write!(f, "Checking"),
+            Self::Savings => write!(f, "Savings"),
+            Self::Credit => write!(f, "Credit"),
+            Self::Cash => write!(f, "Cash"),
+            Self::Loan => write!(f, "Loan"),
+        }
+    }
+}
+
+impl std::str::FromStr for AccountKind {
+    type Err = AccountErr;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_uppercase().as_str() {
+            "CHECKING" => Ok(Self::Checking),
+            "SAVINGS" => Ok(Self::Savings),
+            "CREDIT" => Ok(Self::Credit),
+            "CASH" => Ok(Self::Cash),
+            "LOAN" => Ok(Self::Loan),
+            _ => Err(AccountErr::KindParse),
+        }
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum AccountErr {
+    #[error("Invalid account kind, expected checking, savings, credit, cash or loan")]
+    KindParse,
+    #[error("No account named \"{0}\"")]
+    NotFound(String),
+    #[error("An account named \"{0}\" already exists")]
+    AlreadyExists(String),
+    #[error("Account \"{0}\" is closed")]
+    Closed(String),
+    #[error("A transfer needs two different accounts")]
+    SameAccount,
+    #[error("Can't transfer between {0} and {1} accounts")]
+    CurrencyMismatch(String, String),
+    #[error("An account needs a name")]
+    EmptyName,
+    #[error("Invalid currency \"{0}\", expected a three-letter code such as USD")]
+    CurrencyParse(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Account {
+    pub name: String,
+    pub kind: AccountKind,
+    pub opening_balance: Money,
+    pub currency: String,
+    #[serde(default)]
+    pub closed: bool,
+}
+
+impl Account {
+    pub fn new(
+        name: &str,
+        kind: AccountKind,
+        opening_balance: Money,
+        currency: &str,
+    ) -> Result<Self, AccountErr> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err(AccountErr::EmptyName);
+        }
+        let currency = currency.trim();
+        if currency.len() != 3 || !currency.bytes().all(|b| b.is_ascii_alphabetic()) {
+            return Err(AccountErr::CurrencyParse(currency.to_string()));
+        }
+        Ok(Account {
+            name: name.to_string(),
+            kind,
+            opening_balance,
+            currency: currency.to_uppercase(),
+            closed: false,
+        })
+    }
+
+    /// `$12.00` / `-$12.00` for US dollars, `12.00 EUR` for anything else.
+    pub fn format_amount(&self, amount: Money) -> String {
+        if self.currency == "USD" {
+            let sign = if amount.is_negative() { "-" } else { "" };
+            format!("{}${}", sign, amount.abs())
+        } else {
+            format!("{} {}", amount, self.currency)
+        }
+    }
+}
+
+pub fn find<'a>(accounts: &'a [Account], name: &str) -> Option<&'a Account> {
+    accounts
+        .iter()
+        .find(|a| a.name.eq_ignore_ascii_case(name.trim()))
+}
+
+/// Look up an account that can still take new transactions.
+pub fn find_open<'a>(accounts: &'a [Account], name: &str) -> Result<&'a Account, AccountErr> {
+    let account = find(accounts, name).ok_or_else(|| AccountErr::NotFound(name.to_string()))?;
+    if account.closed {
+        return Err(AccountErr::Closed(account.name.clone()));
+    }
+    Ok(account)
+}
+
+/// Check that money can move from `from` to `to`: both open, distinct and
+/// in the same currency.
+pub fn transfer_accounts<'a>(
+    accounts: &'a [Account],
+    from: &str,
+    to: &str,
+) -> Result<(&'a Account, &'a Account), AccountErr> {
+    let from = find_open(accounts, from)?;
+    let to = find_open(accounts, to)?;
+
+    if from.name == to.name {
+        return Err(AccountErr::SameAccount);
+    }
+    if from.currency != to.currency {
+        return Err(AccountErr::CurrencyMismatch(
+            from.currency.clone(),
+            to.currency.clone(),
+        ));
+    }
+    Ok((from, to))
+}
+
+pub fn add(accounts: &mut Vec<Account>, account: Account) -> Result<(), AccountErr> {
+    if find(accounts, &account.name).is_some() {
+        return Err(AccountErr::AlreadyExists(account.name));
+    }
+    accounts.push(account);
+    Ok(())
+}
+
+pub fn close(accounts: &mut [Account], name: &str) -> Result<(), AccountErr> {
+    let account = accounts
+        .iter_mut()
+        .find(|a| a.name.eq_ignore_ascii_case(name.trim()))
+        .ok_or_else(|| AccountErr::NotFound(name.to_string()))?;
+    account.closed = true;
+    Ok(())
+}
+
+/// Balance of each account after every one of its transactions, keyed by
+/// transaction id. Transactions are applied in date order, then id order.
+pub fn running_balances(accounts: &[Account], transactions: &[Transaction]) -> HashMap<u32, Money> {
+    let mut ordered: Vec<&Transaction> = transactions.iter().collect();
+    ordered.sort_by_key(|t| (t.date(), t.id()));
+
+    let mut current: HashMap<String, Money> = accounts
+        .iter()
+        .map(|a| (a.name.to_lowercase(), a.opening_balance))
+        .collect();
+    let mut balances = HashMap::new();
+
+    for transaction in ordered {
+        let Some(name) = transaction.account() else {
+            continue;
+        };
+        if let Some(balance) = current.get_mut(&name.to_lowercase()) {
+            *balance += transaction.signed_amount();
+            balances.insert(transaction.id(), *balance);
+        }
+    }
+
+    balances
+}
+
+/// [`running_balances`] formatted in each account's currency, for display.
+pub fn balance_labels(accounts: &[Account], transactions: &[Transaction]) -> HashMap<u32, String> {
+    let balances = running_balances(accounts, transactions);
+
+    transactions
+        .iter()
+        .filter_map(|t| {
+            let account = find(accounts, t.account()?)?;
+            let balance = balances.get(&t.id())?;
+            Some((t.id(), account.format_amount(*balance)))
+        })
+        .collect()
+}
+
+/// Current balance of an account: opening balance plus all its transactions.
+pub fn balance(account: &Account, transactions: &[Transaction]) -> Money {
+    balance_on(account, transactions, NaiveDate::MAX)
+}
+
+/// Balance of an account at the end of `date`.
+pub fn balance_on(account: &Account, transactions: &[Transaction], date: NaiveDate) -> Money {
+    account.opening_balance
+        + transactions
+            .iter()
+            .filter(|t| {
+                t.date() <= date
+                    && t.account()
+                        .is_some_and(|name| name.eq_ignore_ascii_case(&account.name))
+            })
+            .map(|t| t.signed_amount())
+            .sum::<Money>()
+}
+
+pub fn print_accounts(accounts: &[Account], transactions: &[Transaction]) {
+    let mut table = Table::new();
+
+    table.add_row(row!["Name", "Kind", "Currency", "Opening", "Balance", "Status"]);
+
+    for account in accounts {
+        table.add_row(Row::new(vec![
+            Cell::new(&account.name),
+            Cell::new(&account.kind.to_string()),
+            Cell::new(&account.currency),
+            Cell::new(&account.format_amount(account.opening_balance)),
+            Cell::new(&account.format_amount(balance(account, transactions))),
+            Cell::new(if account.closed { "Closed" } else { "Open" }),
+        ]));
+    }
+    table.printstd();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{money, transaction};
+
+    fn checking() -> Account {
+        Account::new("Checking", AccountKind::Checking, money("100.00"), "usd").unwrap()
+    }
+
+    fn trans(id: u32, date: &str, amount: &str, cat: &str, account: Option<&str>) -> Transaction {
+        transaction(id, date, amount, cat).with_account(account.map(String::from))
+    }
+
+    #[test]
+    fn test_account_kind_parsing() {
+        assert_eq!("credit".parse::<AccountKind>().unwrap(), AccountKind::Credit);
+        assert_eq!("LOAN".parse::<AccountKind>().unwrap(), AccountKind::Loan);
+        assert!("brokerage".parse::<AccountKind>().is_err());
+    }
+
+    #[test]
+    fn test_add_rejects_duplicate_name() {
+        let mut accounts = vec![checking()];
+        let result = add(
+            &mut accounts,
+            Account::new("checking", AccountKind::Savings, Money::ZERO, "USD").unwrap(),
+        );
+
+        assert!(matches!(result, Err(AccountErr::AlreadyExists(_))));
+        assert_eq!(accounts.len(), 1);
+    }
+
+    #[test]
+    fn test_closed_account_is_not_open() {
+        let mut accounts = vec![checking()];
+        close(&mut accounts, "CHECKING").unwrap();
+
+        assert!(accounts[0].closed);
+        assert!(matches!(find_open(&accounts, "checking"), Err(AccountErr::Closed(_))));
+        assert!(matches!(find_open(&accounts, "savings"), Err(AccountErr::NotFound(_))));
+    }
+
+    #[test]
+    fn test_running_balances_follow_date_order() {
+        let accounts = vec![checking()];
+        let transactions = vec![
+            trans(1, "01/10/2024", "30.00", "Food", Some("Checking")),
+            trans(2, "01/05/2024", "500.00", "Salary", Some("checking")),
+            trans(3, "01/07/2024", "20.00", "Food", None),
+            trans(4, "01/12/2024", "10.00", "Food", Some("Checking")),
+        ];
+
+        let balances = running_balances(&accounts, &transactions);
+
+        assert_eq!(balances.get(&2), Some(&money("600.00")));
+        assert_eq!(balances.get(&1), Some(&money("570.00")));
+        assert_eq!(balances.get(&4), Some(&money("560.00")));
+        assert_eq!(balances.get(&3), None);
+        assert_eq!(balance(&accounts[0], &transactions), money("560.00"));
+        let jan_10 = NaiveDate::from_ymd_opt(2024, 1, 10).unwrap();
+        assert_eq!(balance_on(&accounts[0], &transactions, jan_10), money("570.00"));
+
+        let labels = balance_labels(&accounts, &transactions);
+        assert_eq!(labels.get(&4).map(String::as_str), Some("$560.00"));
+        assert_eq!(labels.get(&3), None);
+    }
+
+    #[test]
+    fn test_transfer_accounts_validation() {
+        let accounts = vec![
+            checking(),
+            Account::new("Savings", AccountKind::Savings, Money::ZERO, "USD").unwrap(),
+            Account::new("Euro", AccountKind::Cash, Money::ZERO, "EUR").unwrap(),
+        ];
+
+        let (from, to) = transfer_accounts(&accounts, "checking", "savings").unwrap();
+        assert_eq!((from.name.as_str(), to.name.as_str()), ("Checking", "Savings"));
+        assert!(matches!(
+            transfer_accounts(&accounts, "Checking", "CHECKING"),
+            Err(AccountErr::SameAccount)
+        ));
+        assert!(matches!(
+            transfer_accounts(&accounts, "Checking", "Euro"),
+            Err(AccountErr::CurrencyMismatch(_, _))
+        ));
+    }
+
+    #[test]
+    fn test_transfer_moves_balance_between_accounts() {
+        let accounts = [
+            checking(),
+            Account::new("Savings", AccountKind::Savings, Money::ZERO, "USD").unwrap(),
+        ];
+        let (out, inbound) = Transaction::transfer_pair(
+            (1, 2),
+            chrono::NaiveDate::from_ymd_opt(2024, 1, 5).unwrap(),
+            money("40.00"),
+            ("Checking", "Savings"),
+            None,
+        )
+        .unwrap();
+        let transactions = vec![out, inbound];
+
+        assert_eq!(balance(&accounts[0], &transactions), money("60.00"));
+        assert_eq!(balance(&accounts[1], &transactions), money("40.00"));
+    }
+
+    #[test]
+    fn test_new_validates_name_and_currency() {
+        let new = |name, currency| Account::new(name, AccountKind::Cash, Money::ZERO, currency);
+
+        assert_eq!(new("  Wallet ", "eur").unwrap().name, "Wallet");
+        assert!(matches!(new("   ", "USD"), Err(AccountErr::EmptyName)));
+        assert!(matches!(new("Wallet", "US"), Err(AccountErr::CurrencyParse(_))));
+        assert!(matches!(new("Wallet", "US1"), Err(AccountErr::CurrencyParse(_))));
+        assert!(matches!(new("Wallet", "dollars"), Err(AccountErr::CurrencyParse(_))));
+    }
+
+    #[test]
+    fn test_format_amount_uses_currency() {
+        let euro = Account::new("Euro", AccountKind::Cash, Money::ZERO, "eur").unwrap();
+        assert_eq!(checking().format_amount(money("5")), "$5.00");
+        assert_eq!(checking().format_amount(money("-5")), "-$5.00");
+        assert_eq!(euro.format_amount(money("5")), "5.00 EUR");
+    }
+}
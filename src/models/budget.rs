@@ -1 +1,511 @@
-//mvp 2 will build this
+use super::category::Category;
+use super::money::Money;
+use super::transaction::Transaction;
+use crate::utils::date_utils::YearMonth;
+use prettytable::{row, Cell, Row, Table};
+use serde::{Deserialize, Serialize};
+
+/// A monthly spending limit for one expense category.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Budget {
+    pub category: Category,
+    pub monthly_limit: Money,
+    /// Every limit set and the month it took effect, oldest first. Budgets
+    /// saved before limits were dated have none, and their limit applies to
+    /// every month.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub changes: Vec<LimitChange>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LimitChange {
+    pub from: YearMonth,
+    pub limit: Money,
+}
+
+impl Budget {
+    /// The limit in force during `month`, or `None` before the budget was
+    /// first set.
+    pub fn limit_in(&self, month: YearMonth) -> Option<Money> {
+        if self.changes.is_empty() {
+            return Some(self.monthly_limit);
+        }
+        self.changes
+            .iter()
+            .rev()
+            .find(|c| c.from <= month)
+            .map(|c| c.limit)
+    }
+
+    /// The month the budget was first set, if its limits are dated.
+    pub fn first_month(&self) -> Option<YearMonth> {
+        self.changes.first().map(|c| c.from)
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum BudgetErr {
+    #[error("No budget set for {0}")]
+    NotFound(String),
+    #[error("{0} is not an expense category")]
+    NotExpense(String),
+    #[error("A budget limit can't be negative")]
+    NegativeLimit,
+    #[error("Warning thresholds must be above 0%")]
+    ZeroThreshold,
+    #[error("This would put {category} ${over} over budget; use --force to add it anyway")]
+    WouldExceed { category: String, over: Money },
+}
+
+/// How a budget stands for one month.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BudgetStatus {
+    pub category: Category,
+    pub limit: Money,
+    pub spent: Money,
+}
+
+impl BudgetStatus {
+    pub fn remaining(&self) -> Money {
+        self.limit - self.spent
+    }
+
+    /// Share of the limit spent, or `None` for a zero limit.
+    pub fn percent_used(&self) -> Option<f64> {
+        self.spent.percent_of(self.limit)
+    }
+}
+
+/// What a new transaction does to one budget.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BudgetImpact {
+    pub before: BudgetStatus,
+    pub added: Money,
+}
+
+impl BudgetImpact {
+    pub fn after(&self) -> BudgetStatus {
+        BudgetStatus {
+            spent: self.before.spent + self.added,
+            ..self.before.clone()
+        }
+    }
+
+    /// The highest threshold (in percent) this transaction takes the budget
+    /// across, if any.
+    pub fn crossed(&self, thresholds: &[u32]) -> Option<u32> {
+        let before = self.before.clone();
+        let after = self.after();
+        thresholds
+            .iter()
+            .copied()
+            .filter(|&t| !reached(&before, t) && reached(&after, t))
+            .max()
+    }
+
+    pub fn warning(&self, threshold: u32) -> String {
+        let after = self.after();
+        if after.spent > after.limit {
+            format!(
+                "Warning: {} is over budget, ${} spent of ${} (${} remaining)",
+                after.category,
+                after.spent,
+                after.limit,
+                after.remaining()
+            )
+        } else {
+            format!(
+                "Warning: {} has reached {}% of its ${} budget (${} remaining)",
+                after.category,
+                threshold,
+                after.limit,
+                after.remaining()
+            )
+        }
+    }
+}
+
+fn reached(status: &BudgetStatus, threshold: u32) -> bool {
+    match status.percent_used() {
+        Some(percent) => percent >= threshold as f64,
+        // anything spent against a zero limit is past every threshold
+        None => status.spent > Money::ZERO,
+    }
+}
+
+/// Sort and de-duplicate warning thresholds.
+pub fn thresholds(raw: &[u32]) -> Result<Vec<u32>, BudgetErr> {
+    if raw.contains(&0) {
+        return Err(BudgetErr::ZeroThreshold);
+    }
+    let mut thresholds = raw.to_vec();
+    thresholds.sort_unstable();
+    thresholds.dedup();
+    Ok(thresholds)
+}
+
+/// How adding `new` would change each budget it spends against, given the
+/// rest of the ledger.
+pub fn impact(
+    budgets: &[Budget],
+    transactions: &[Transaction],
+    new: &Transaction,
+) -> Vec<BudgetImpact> {
+    if new.fund().is_some() {
+        return Vec::new();
+    }
+    let month = YearMonth::of(new.date());
+    let lines = new.category_lines();
+
+    budgets
+        .iter()
+        .filter_map(|b| {
+            let limit = b.limit_in(month)?;
+            let added: Money = lines
+                .iter()
+                .filter(|(c, _)| c.same(&b.category))
+                .map(|(_, amount)| *amount)
+                .sum();
+            if added <= Money::ZERO {
+                return None;
+            }
+            Some(BudgetImpact {
+                before: BudgetStatus {
+                    category: b.category.clone(),
+                    limit,
+                    spent: spent(&b.category, month, transactions),
+                },
+                added,
+            })
+        })
+        .collect()
+}
+
+/// Refuse a transaction that would leave any budget overspent.
+pub fn check_strict(impacts: &[BudgetImpact]) -> Result<(), BudgetErr> {
+    for impact in impacts {
+        let after = impact.after();
+        if after.spent > after.limit {
+            return Err(BudgetErr::WouldExceed {
+                category: after.category.to_string(),
+                over: after.spent - after.limit,
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Add a budget, or replace the limit of an existing one, from `month` on.
+/// Earlier months keep the limit they had; for a budget from before limits
+/// were dated, that is its old limit.
+pub fn set(
+    budgets: &mut Vec<Budget>,
+    category: Category,
+    limit: Money,
+    month: YearMonth,
+) -> Result<(), BudgetErr> {
+    if category.is_income() || category.is_transfer() {
+        return Err(BudgetErr::NotExpense(category.to_string()));
+    }
+    if limit.is_negative() {
+        return Err(BudgetErr::NegativeLimit);
+    }
+
+    let change = LimitChange { from: month, limit };
+    match budgets.iter_mut().find(|b| b.category.same(&category)) {
+        Some(existing) => {
+            if existing.changes.is_empty() {
+                existing.changes.push(LimitChange {
+                    from: YearMonth::MIN,
+                    limit: existing.monthly_limit,
+                });
+            }
+            existing.monthly_limit = limit;
+            // a second change in the same month replaces the first
+            existing.changes.retain(|c| c.from < month);
+            existing.changes.push(change);
+        }
+        None => budgets.push(Budget {
+            category,
+            monthly_limit: limit,
+            changes: vec![change],
+        }),
+    }
+    Ok(())
+}
+
+pub fn remove(budgets: &mut Vec<Budget>, category: &Category) -> Result<(), BudgetErr> {
+    let pos = budgets
+        .iter()
+        .position(|b| b.category.same(category))
+        .ok_or_else(|| BudgetErr::NotFound(category.to_string()))?;
+    budgets.remove(pos);
+    Ok(())
+}
+
+/// Total spent in `category` during `month`, counting each split line on
+/// its own. Payments drawn from a sinking fund are left out.
+pub fn spent(category: &Category, month: YearMonth, transactions: &[Transaction]) -> Money {
+    transactions
+        .iter()
+        .filter(|t| month.contains(t.date()) && t.fund().is_none())
+        .flat_map(|t| t.category_lines())
+        .filter(|(c, _)| c.same(category))
+        .map(|(_, amount)| amount)
+        .sum()
+}
+
+/// How each budget in force during `month` stands.
+pub fn status(
+    budgets: &[Budget],
+    transactions: &[Transaction],
+    month: YearMonth,
+) -> Vec<BudgetStatus> {
+    budgets
+        .iter()
+        .filter_map(|b| {
+            Some(BudgetStatus {
+                category: b.category.clone(),
+                limit: b.limit_in(month)?,
+                spent: spent(&b.category, month, transactions),
+            })
+        })
+        .collect()
+}
+
+pub fn print_budgets(budgets: &[Budget]) {
+    let mut table = Table::new();
+
+    table.add_row(row!["Category", "Monthly Limit"]);
+
+    for budget in budgets {
+        table.add_row(Row::new(vec![
+            Cell::new(&budget.category.to_string()),
+            Cell::new(&format!("${}", budget.monthly_limit)),
+        ]));
+    }
+    table.printstd();
+}
+
+pub fn print_status(statuses: &[BudgetStatus], month: YearMonth) {
+    let mut table = Table::new();
+
+    table.add_row(row!["Category", "Limit", "Spent", "Remaining", "% Used"]);
+
+    for status in statuses {
+        let percent = status
+            .percent_used()
+            .map(|p| format!("{:.0}%", p))
+            .unwrap_or_else(|| "-".to_string());
+        let percent_cell = if status.spent > status.limit {
+            Cell::new(&percent).style_spec("Fr")
+        } else {
+            Cell::new(&percent)
+        };
+
+        table.add_row(Row::new(vec![
+            Cell::new(&status.category.to_string()),
+            Cell::new(&format!("${}", status.limit)),
+            Cell::new(&format!("${}", status.spent)),
+            Cell::new(&format!("${}", status.remaining())),
+            percent_cell,
+        ]));
+    }
+
+    println!("Budget status for {}", month);
+    table.printstd();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{money, month, transaction};
+
+    fn march() -> YearMonth {
+        month("2024-03")
+    }
+
+    fn ledger() -> Vec<Transaction> {
+        vec![
+            transaction(1, "03/02/2024", "25.50", "Food"),
+            transaction(2, "03/15/2024", "2000.00", "Salary"),
+            transaction(3, "02/28/2024", "60.00", "Food"),
+            Transaction::new_split(
+                4,
+                "03/20/2024",
+                "65.50",
+                &["Food=40".to_string(), "Shopping=25.50".to_string()],
+                None,
+            )
+            .unwrap(),
+            transaction(5, "03/21/2024", "12.00", "expense:Pet Care"),
+        ]
+    }
+
+    #[test]
+    fn test_set_replaces_existing_limit() {
+        let mut budgets = Vec::new();
+        set(&mut budgets, Category::Food, money("300"), march()).unwrap();
+        set(&mut budgets, Category::Food, money("350"), march()).unwrap();
+
+        assert_eq!(budgets.len(), 1);
+        assert_eq!(budgets[0].monthly_limit, money("350"));
+    }
+
+    #[test]
+    fn test_limit_changes_keep_earlier_months() {
+        let may: YearMonth = "2024-05".parse().unwrap();
+        let mut budgets = Vec::new();
+        set(&mut budgets, Category::Food, money("300"), march()).unwrap();
+        set(&mut budgets, Category::Food, money("400"), may).unwrap();
+        set(&mut budgets, Category::Food, money("350"), may).unwrap();
+
+        let food = &budgets[0];
+        assert_eq!(food.changes.len(), 2);
+        assert_eq!(food.limit_in(march().add_months(-1)), None);
+        assert_eq!(food.limit_in(march().next()), Some(money("300")));
+        assert_eq!(food.limit_in(may.next()), Some(money("350")));
+        assert_eq!(food.first_month(), Some(march()));
+        assert!(status(&budgets, &ledger(), march().add_months(-1)).is_empty());
+    }
+
+    #[test]
+    fn test_undated_budget_applies_to_every_month() {
+        let budget: Budget =
+            serde_json::from_str(r#"{"category":"Food","monthly_limit":"50.00"}"#).unwrap();
+        assert_eq!(budget.limit_in(march()), Some(money("50")));
+        assert_eq!(budget.first_month(), None);
+    }
+
+    #[test]
+    fn test_set_on_undated_budget_keeps_earlier_months() {
+        let budget: Budget =
+            serde_json::from_str(r#"{"category":"Food","monthly_limit":"50.00"}"#).unwrap();
+        let mut budgets = vec![budget];
+        set(&mut budgets, Category::Food, money("80"), month("2024-05")).unwrap();
+
+        let food = &budgets[0];
+        assert_eq!(food.limit_in(march()), Some(money("50")));
+        assert_eq!(food.limit_in(month("2020-01")), Some(money("50")));
+        assert_eq!(food.limit_in(month("2024-05")), Some(money("80")));
+        assert_eq!(food.monthly_limit, money("80"));
+    }
+
+    #[test]
+    fn test_set_rejects_income_category() {
+        let mut budgets = Vec::new();
+        let result = set(&mut budgets, Category::Salary, money("100"), march());
+        assert!(matches!(result, Err(BudgetErr::NotExpense(_))));
+    }
+
+    #[test]
+    fn test_remove() {
+        let mut budgets = Vec::new();
+        set(&mut budgets, Category::Food, money("300"), march()).unwrap();
+
+        assert!(matches!(
+            remove(&mut budgets, &Category::Bills),
+            Err(BudgetErr::NotFound(_))
+        ));
+        remove(&mut budgets, &Category::Food).unwrap();
+        assert!(budgets.is_empty());
+    }
+
+    #[test]
+    fn test_spent_counts_split_lines_in_month() {
+        let transactions = ledger();
+        assert_eq!(
+            spent(&Category::Food, march(), &transactions),
+            money("65.50")
+        );
+        assert_eq!(
+            spent(&Category::Shopping, march(), &transactions),
+            money("25.50")
+        );
+    }
+
+    #[test]
+    fn test_spent_custom_category_ignores_case() {
+        let transactions = ledger();
+        let pet_care: Category = "expense:pet care".parse().unwrap();
+        assert_eq!(spent(&pet_care, march(), &transactions), money("12.00"));
+    }
+
+    #[test]
+    fn test_impact_crosses_highest_threshold() {
+        let transactions = ledger();
+        let mut budgets = Vec::new();
+        set(&mut budgets, Category::Food, money("100"), march()).unwrap();
+
+        let new = Transaction::new(9, "03/25/2024", "20", "Food", None).unwrap();
+        let impacts = impact(&budgets, &transactions, &new);
+
+        // 65.50 -> 85.50
+        assert_eq!(impacts.len(), 1);
+        assert_eq!(impacts[0].after().remaining(), money("14.50"));
+        assert_eq!(impacts[0].crossed(&[50, 80, 100]), Some(80));
+        assert!(check_strict(&impacts).is_ok());
+
+        let big = Transaction::new(9, "03/25/2024", "50", "Food", None).unwrap();
+        let impacts = impact(&budgets, &transactions, &big);
+        assert_eq!(impacts[0].crossed(&[80, 100]), Some(100));
+        assert!(matches!(
+            check_strict(&impacts),
+            Err(BudgetErr::WouldExceed { over, .. }) if over == money("15.50")
+        ));
+    }
+
+    #[test]
+    fn test_impact_ignores_other_months_and_categories() {
+        let transactions = ledger();
+        let mut budgets = Vec::new();
+        set(&mut budgets, Category::Food, money("100"), march()).unwrap();
+
+        let april = Transaction::new(9, "04/01/2024", "20", "Food", None).unwrap();
+        let impacts = impact(&budgets, &transactions, &april);
+        assert_eq!(impacts[0].before.spent, Money::ZERO);
+        assert_eq!(impacts[0].crossed(&[80, 100]), None);
+
+        let bills = Transaction::new(9, "03/25/2024", "20", "Bills", None).unwrap();
+        assert!(impact(&budgets, &transactions, &bills).is_empty());
+    }
+
+    #[test]
+    fn test_sinking_fund_payments_are_not_budget_spending() {
+        let mut transactions = ledger();
+        let mut budgets = Vec::new();
+        set(&mut budgets, Category::Food, money("100"), march()).unwrap();
+
+        let drawn = Transaction::new(9, "03/25/2024", "500", "Food", None)
+            .unwrap()
+            .with_fund(Some("Holidays".to_string()));
+        assert!(impact(&budgets, &transactions, &drawn).is_empty());
+
+        transactions.push(drawn);
+        assert_eq!(spent(&Category::Food, march(), &transactions), money("65.50"));
+    }
+
+    #[test]
+    fn test_thresholds_sorted_and_positive() {
+        assert_eq!(thresholds(&[100, 80, 80]).unwrap(), vec![80, 100]);
+        assert!(matches!(
+            thresholds(&[0, 50]),
+            Err(BudgetErr::ZeroThreshold)
+        ));
+    }
+
+    #[test]
+    fn test_status() {
+        let transactions = ledger();
+        let mut budgets = Vec::new();
+        set(&mut budgets, Category::Food, money("50"), march()).unwrap();
+        set(&mut budgets, Category::Bills, Money::ZERO, march()).unwrap();
+
+        let statuses = status(&budgets, &transactions, march());
+
+        assert_eq!(statuses[0].spent, money("65.50"));
+        assert_eq!(statuses[0].remaining(), money("-15.50"));
+        assert_eq!(statuses[0].percent_used(), Some(131.0));
+        assert_eq!(statuses[1].percent_used(), None);
+    }
+}
@@ -14,6 +14,8 @@ pub enum Category {
     Salary,
     Investment,
     Freelance,
+    /// Money moved between two accounts; neither income nor expense.
+    Transfer,
     Custom {
         income_or_expense: String,
         name: String,
@@ -29,18 +31,37 @@ impl Category {
             Self::Custom {
                 income_or_expense,
                 name: _,
-            } => {
-                if income_or_expense.eq_ignore_ascii_case("income") {
-                    true
-                } else {
-                    false
-                }
-            }
+            } => income_or_expense.eq_ignore_ascii_case("income"),
             _ => false,
         }
     }
 
+    /// Equality that ignores the case of custom category names, since
+    /// users type them by hand.
+    pub fn same(&self, other: &Category) -> bool {
+        match (self, other) {
+            (
+                Self::Custom {
+                    income_or_expense: a_kind,
+                    name: a_name,
+                },
+                Self::Custom {
+                    income_or_expense: b_kind,
+                    name: b_name,
+                },
+            ) => a_kind.eq_ignore_ascii_case(b_kind) && a_name.eq_ignore_ascii_case(b_name),
+            _ => self == other,
+        }
+    }
+
+    pub fn is_transfer(&self) -> bool {
+        matches!(self, Self::Transfer)
+    }
+
     pub fn category_type(&self) -> &'static str {
+        if self.is_transfer() {
+            return "Transfer";
+        }
         match self.is_income() {
             true => "Income",
             false => "Expense",
@@ -61,6 +82,7 @@ impl fmt::Display for Category {
             Self::Salary => write!(f, "Salary"),
             Self::Investment => write!(f, "Investment"),
             Self::Freelance => write!(f, "Freelance"),
+            Self::Transfer => write!(f, "Transfer"),
             Self::Custom {
                 income_or_expense: _,
                 name,
@@ -276,6 +298,34 @@ mod tests {
         assert_eq!(expense_custom.category_type(), "Expense");
     }
 
+    #[test]
+    fn test_transfer_is_neither_income_nor_expense() {
+        assert!(!Category::Transfer.is_income());
+        assert!(Category::Transfer.is_transfer());
+        assert!(!Category::Food.is_transfer());
+        assert_eq!(Category::Transfer.category_type(), "Transfer");
+        assert_eq!(Category::Transfer.to_string(), "Transfer");
+    }
+
+    #[test]
+    fn test_transfer_cannot_be_typed_as_category() {
+        // transfers only come from the transfer command, which links both legs
+        let result: Result<Category, _> = "Transfer".parse();
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_same_ignores_custom_name_case() {
+        let a: Category = "expense:Pet Care".parse().unwrap();
+        let b: Category = "EXPENSE:pet care".parse().unwrap();
+        let c: Category = "income:Pet Care".parse().unwrap();
+
+        assert!(a.same(&b));
+        assert!(!a.same(&c));
+        assert!(Category::Food.same(&Category::Food));
+        assert!(!Category::Food.same(&Category::Bills));
+    }
+
     #[test]
     fn test_category_serialization() {
         let category = Category::Food;
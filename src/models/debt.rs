@@ -0,0 +1,138 @@
+use super::money::Money;
+use super::rate::Rate;
+use prettytable::{row, Cell, Row, Table};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, thiserror::Error)]
+pub enum DebtErr {
+    #[error("No debt named \"{0}\"")]
+    NotFound(String),
+    #[error("A debt named \"{0}\" already exists")]
+    AlreadyExists(String),
+    #[error("A debt's balance must be more than zero")]
+    NonPositiveBalance,
+    #[error("A debt's minimum payment must be more than zero")]
+    NonPositiveMinimum,
+    #[error("A debt needs a name")]
+    EmptyName,
+}
+
+/// A loan or credit card being paid down.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Debt {
+    pub name: String,
+    pub balance: Money,
+    pub apr: Rate,
+    pub minimum: Money,
+}
+
+impl Debt {
+    pub fn new(name: &str, balance: Money, apr: Rate, minimum: Money) -> Result<Self, DebtErr> {
+        if name.trim().is_empty() {
+            return Err(DebtErr::EmptyName);
+        }
+        if balance <= Money::ZERO {
+            return Err(DebtErr::NonPositiveBalance);
+        }
+        if minimum <= Money::ZERO {
+            return Err(DebtErr::NonPositiveMinimum);
+        }
+        Ok(Debt {
+            name: name.trim().to_string(),
+            balance,
+            apr,
+            minimum,
+        })
+    }
+}
+
+pub fn find<'a>(debts: &'a [Debt], name: &str) -> Result<&'a Debt, DebtErr> {
+    debts
+        .iter()
+        .find(|d| d.name.eq_ignore_ascii_case(name.trim()))
+        .ok_or_else(|| DebtErr::NotFound(name.trim().to_string()))
+}
+
+pub fn add(debts: &mut Vec<Debt>, debt: Debt) -> Result<(), DebtErr> {
+    if find(debts, &debt.name).is_ok() {
+        return Err(DebtErr::AlreadyExists(debt.name));
+    }
+    debts.push(debt);
+    Ok(())
+}
+
+pub fn remove(debts: &mut Vec<Debt>, name: &str) -> Result<(), DebtErr> {
+    let name = find(debts, name)?.name.clone();
+    debts.retain(|d| d.name != name);
+    Ok(())
+}
+
+pub fn print_debts(debts: &[Debt]) {
+    let mut table = Table::new();
+
+    table.add_row(row!["Name", "Balance", "APR", "Minimum"]);
+
+    for debt in debts {
+        table.add_row(Row::new(vec![
+            Cell::new(&debt.name),
+            Cell::new(&format!("${}", debt.balance)),
+            Cell::new(&debt.apr.to_string()),
+            Cell::new(&format!("${}", debt.minimum)),
+        ]));
+    }
+    let total: Money = debts.iter().map(|d| d.balance).sum();
+    let minimums: Money = debts.iter().map(|d| d.minimum).sum();
+    table.add_row(row![
+        "Total",
+        format!("${}", total),
+        "",
+        format!("${}", minimums)
+    ]);
+    table.printstd();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{money, rate};
+
+    fn debt(name: &str) -> Debt {
+        Debt::new(name, money("1000"), rate("19.99"), money("35")).unwrap()
+    }
+
+    #[test]
+    fn test_new_validates_name_and_amounts() {
+        let apr = rate("5");
+        assert!(matches!(
+            Debt::new("Car", Money::ZERO, apr, money("100")),
+            Err(DebtErr::NonPositiveBalance)
+        ));
+        assert!(matches!(
+            Debt::new("Car", money("100"), apr, Money::ZERO),
+            Err(DebtErr::NonPositiveMinimum)
+        ));
+        assert!(matches!(
+            Debt::new("", money("100"), apr, money("10")),
+            Err(DebtErr::EmptyName)
+        ));
+    }
+
+    #[test]
+    fn test_add_find_and_remove() {
+        let mut debts = vec![debt("Visa")];
+        add(&mut debts, debt("Car loan")).unwrap();
+
+        assert!(matches!(
+            add(&mut debts, debt("visa")),
+            Err(DebtErr::AlreadyExists(_))
+        ));
+        assert_eq!(find(&debts, " car LOAN ").unwrap().name, "Car loan");
+
+        remove(&mut debts, "VISA").unwrap();
+        assert_eq!(debts.len(), 1);
+        assert!(matches!(
+            remove(&mut debts, "Visa"),
+            Err(DebtErr::NotFound(_))
+        ));
+    }
+}
@@ -0,0 +1,437 @@
+use super::budget;
+use super::category::Category;
+use super::money::Money;
+use super::transaction::Transaction;
+use crate::utils::date_utils::YearMonth;
+use prettytable::{row, Cell, Row, Table};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// What happens to money left in an envelope at the end of a month.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum Rollover {
+    /// Keep the balance for next month
+    #[default]
+    Carry,
+    /// Return unspent money; an overspent balance still carries
+    Sweep,
+}
+
+impl fmt::Display for Rollover {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Carry => write!(f, "Carry"),
+            Self::Sweep => write!(f, "Sweep"),
+        }
+    }
+}
+
+impl std::str::FromStr for Rollover {
+    type Err = EnvelopeErr;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_uppercase().as_str() {
+            "CARRY" => Ok(Self::Carry),
+            "SWEEP" => Ok(Self::Sweep),
+            _ => Err(EnvelopeErr::RolloverParse),
+        }
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum EnvelopeErr {
+    #[error("Invalid rollover, expected carry or sweep")]
+    RolloverParse,
+    #[error("No envelope for {0}")]
+    NotFound(String),
+    #[error("{0} is not an expense category")]
+    NotExpense(String),
+    #[error("An envelope can't borrow from itself")]
+    SelfBorrow,
+}
+
+/// Money put into an envelope for one month. Negative amounts take money
+/// back out.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Funding {
+    pub month: YearMonth,
+    pub amount: Money,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Envelope {
+    pub category: Category,
+    #[serde(default)]
+    pub rollover: Rollover,
+    /// Envelope that covers this one when it is overspent
+    #[serde(default)]
+    pub borrow_from: Option<Category>,
+    #[serde(default)]
+    pub funding: Vec<Funding>,
+}
+
+impl Envelope {
+    pub fn new(category: Category) -> Result<Self, EnvelopeErr> {
+        if category.is_income() || category.is_transfer() {
+            return Err(EnvelopeErr::NotExpense(category.to_string()));
+        }
+        Ok(Envelope {
+            category,
+            rollover: Rollover::default(),
+            borrow_from: None,
+            funding: Vec::new(),
+        })
+    }
+
+    pub fn funded_in(&self, month: YearMonth) -> Money {
+        self.funding
+            .iter()
+            .filter(|f| f.month == month)
+            .map(|f| f.amount)
+            .sum()
+    }
+
+    fn first_month(&self) -> Option<YearMonth> {
+        self.funding.iter().map(|f| f.month).min()
+    }
+}
+
+/// One envelope's movements over a month.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EnvelopeRow {
+    pub category: Category,
+    pub rollover: Rollover,
+    pub carry_in: Money,
+    pub funded: Money,
+    pub spent: Money,
+    /// Positive when covered by another envelope, negative when lending
+    pub borrowed: Money,
+    /// Last month's surplus returned by a sweeping envelope
+    pub swept: Money,
+    pub balance: Money,
+}
+
+fn position(envelopes: &[Envelope], category: &Category) -> Option<usize> {
+    envelopes.iter().position(|e| e.category.same(category))
+}
+
+/// The first month the envelope for `category` was funded, if any.
+pub fn first_funded(envelopes: &[Envelope], category: &Category) -> Option<YearMonth> {
+    position(envelopes, category).and_then(|pos| envelopes[pos].first_month())
+}
+
+fn find_or_create(
+    envelopes: &mut Vec<Envelope>,
+    category: Category,
+) -> Result<&mut Envelope, EnvelopeErr> {
+    let pos = match position(envelopes, &category) {
+        Some(pos) => pos,
+        None => {
+            envelopes.push(Envelope::new(category)?);
+            envelopes.len() - 1
+        }
+    };
+    Ok(&mut envelopes[pos])
+}
+
+/// Put `amount` into the envelope for `category`, creating it if needed.
+pub fn fund(
+    envelopes: &mut Vec<Envelope>,
+    category: Category,
+    month: YearMonth,
+    amount: Money,
+) -> Result<(), EnvelopeErr> {
+    let envelope = find_or_create(envelopes, category)?;
+    match envelope.funding.iter_mut().find(|f| f.month == month) {
+        Some(existing) => existing.amount += amount,
+        None => envelope.funding.push(Funding { month, amount }),
+    }
+    Ok(())
+}
+
+/// Change how an envelope rolls over and which envelope it borrows from.
+/// `borrow_from` of `Some(None)` stops borrowing.
+pub fn configure(
+    envelopes: &mut Vec<Envelope>,
+    category: Category,
+    rollover: Option<Rollover>,
+    borrow_from: Option<Option<Category>>,
+) -> Result<(), EnvelopeErr> {
+    if let Some(Some(source)) = &borrow_from {
+        if source.same(&category) {
+            return Err(EnvelopeErr::SelfBorrow);
+        }
+        if position(envelopes, source).is_none() {
+            return Err(EnvelopeErr::NotFound(source.to_string()));
+        }
+    }
+
+    let envelope = find_or_create(envelopes, category)?;
+    if let Some(rollover) = rollover {
+        envelope.rollover = rollover;
+    }
+    if let Some(borrow_from) = borrow_from {
+        envelope.borrow_from = borrow_from;
+    }
+    Ok(())
+}
+
+/// Where each envelope stands in `month`.
+pub fn month_rows(
+    envelopes: &[Envelope],
+    transactions: &[Transaction],
+    month: YearMonth,
+) -> Vec<EnvelopeRow> {
+    history(envelopes, transactions, month)
+        .pop()
+        .map(|(_, rows)| rows)
+        .unwrap_or_default()
+}
+
+/// Walk every month from the first funding up to `month`, returning each
+/// month's rows in order.
+///
+/// Within a month, an overspent envelope borrows from its `borrow_from`
+/// envelope, but never more than that envelope has left after its own
+/// spending.
+pub fn history(
+    envelopes: &[Envelope],
+    transactions: &[Transaction],
+    month: YearMonth,
+) -> Vec<(YearMonth, Vec<EnvelopeRow>)> {
+    let mut months = Vec::new();
+    let mut closing = vec![Money::ZERO; envelopes.len()];
+    let mut current = envelopes
+        .iter()
+        .filter_map(Envelope::first_month)
+        .min()
+        .unwrap_or(month)
+        .min(month);
+
+    loop {
+        let mut rows: Vec<EnvelopeRow> = envelopes
+            .iter()
+            .zip(&closing)
+            .map(|(envelope, &previous)| {
+                let carry_in = match envelope.rollover {
+                    Rollover::Carry => previous,
+                    Rollover::Sweep => previous.min(Money::ZERO),
+                };
+                let swept = previous - carry_in;
+                // spending before an envelope is first funded isn't its concern
+                let spent = match envelope.first_month() {
+                    Some(first) if first <= current => {
+                        budget::spent(&envelope.category, current, transactions)
+                    }
+                    _ => Money::ZERO,
+                };
+                let funded = envelope.funded_in(current);
+                EnvelopeRow {
+                    category: envelope.category.clone(),
+                    rollover: envelope.rollover,
+                    carry_in,
+                    funded,
+                    spent,
+                    borrowed: Money::ZERO,
+                    swept,
+                    balance: carry_in + funded - spent,
+                }
+            })
+            .collect();
+
+        for i in 0..envelopes.len() {
+            let Some(source) = &envelopes[i].borrow_from else {
+                continue;
+            };
+            let Some(j) = position(envelopes, source) else {
+                continue;
+            };
+            if !rows[i].balance.is_negative() || j == i {
+                continue;
+            }
+            let take = (-rows[i].balance).min(rows[j].balance.max(Money::ZERO));
+            rows[i].borrowed += take;
+            rows[i].balance += take;
+            rows[j].borrowed -= take;
+            rows[j].balance -= take;
+        }
+
+        closing = rows.iter().map(|r| r.balance).collect();
+        months.push((current, rows));
+        if current >= month {
+            return months;
+        }
+        current = current.next();
+    }
+}
+
+pub fn print_envelopes(rows: &[EnvelopeRow], month: YearMonth) {
+    let mut table = Table::new();
+
+    table.add_row(row![
+        "Envelope", "Rollover", "Carry-in", "Funded", "Spent", "Borrowed", "Balance"
+    ]);
+
+    for r in rows {
+        let balance = format!("${}", r.balance);
+        let balance_cell = if r.balance.is_negative() {
+            Cell::new(&balance).style_spec("Fr")
+        } else {
+            Cell::new(&balance)
+        };
+
+        table.add_row(Row::new(vec![
+            Cell::new(&r.category.to_string()),
+            Cell::new(&r.rollover.to_string()),
+            Cell::new(&format!("${}", r.carry_in)),
+            Cell::new(&format!("${}", r.funded)),
+            Cell::new(&format!("${}", r.spent)),
+            Cell::new(&format!("${}", r.borrowed)),
+            balance_cell,
+        ]));
+    }
+
+    println!("Envelopes for {}", month);
+    table.printstd();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{funded, money, month, transaction};
+
+    #[test]
+    fn test_rollover_parsing() {
+        assert_eq!("carry".parse::<Rollover>().unwrap(), Rollover::Carry);
+        assert_eq!("SWEEP".parse::<Rollover>().unwrap(), Rollover::Sweep);
+        assert!("keep".parse::<Rollover>().is_err());
+    }
+
+    #[test]
+    fn test_fund_accumulates_per_month() {
+        let envelopes = funded(&[
+            (Category::Food, "2024-03", "200"),
+            (Category::Food, "2024-03", "50"),
+            (Category::Food, "2024-04", "100"),
+        ]);
+
+        assert_eq!(envelopes.len(), 1);
+        assert_eq!(envelopes[0].funded_in(month("2024-03")), money("250"));
+        assert_eq!(envelopes[0].funded_in(month("2024-04")), money("100"));
+    }
+
+    #[test]
+    fn test_fund_rejects_income_category() {
+        let mut envelopes = Vec::new();
+        let result = fund(
+            &mut envelopes,
+            Category::Salary,
+            month("2024-03"),
+            money("10"),
+        );
+        assert!(matches!(result, Err(EnvelopeErr::NotExpense(_))));
+    }
+
+    #[test]
+    fn test_configure_validates_borrow_source() {
+        let mut envelopes = Vec::new();
+        assert!(matches!(
+            configure(
+                &mut envelopes,
+                Category::Food,
+                None,
+                Some(Some(Category::Food))
+            ),
+            Err(EnvelopeErr::SelfBorrow)
+        ));
+        assert!(matches!(
+            configure(
+                &mut envelopes,
+                Category::Food,
+                None,
+                Some(Some(Category::Shopping))
+            ),
+            Err(EnvelopeErr::NotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_carry_rolls_unspent_balance_forward() {
+        let envelopes = funded(&[
+            (Category::Food, "2024-03", "200"),
+            (Category::Food, "2024-04", "200"),
+        ]);
+        let transactions = [
+            transaction(1, "03/10/2024", "150", "Food"),
+            transaction(2, "04/10/2024", "100", "Food"),
+        ];
+
+        let rows = month_rows(&envelopes, &transactions, month("2024-04"));
+
+        assert_eq!(rows[0].carry_in, money("50"));
+        assert_eq!(rows[0].spent, money("100"));
+        assert_eq!(rows[0].balance, money("150"));
+    }
+
+    #[test]
+    fn test_sweep_returns_surplus_but_keeps_deficit() {
+        let mut envelopes = funded(&[
+            (Category::Food, "2024-03", "100"),
+            (Category::Bills, "2024-03", "100"),
+        ]);
+        for category in [Category::Food, Category::Bills] {
+            configure(&mut envelopes, category, Some(Rollover::Sweep), None).unwrap();
+        }
+        let transactions = [
+            transaction(1, "03/10/2024", "40", "Food"),
+            transaction(2, "03/10/2024", "130", "Bills"),
+        ];
+
+        let rows = month_rows(&envelopes, &transactions, month("2024-04"));
+
+        assert_eq!(rows[0].carry_in, Money::ZERO);
+        assert_eq!(rows[0].swept, money("60"));
+        assert_eq!(rows[1].carry_in, money("-30"));
+        assert_eq!(rows[1].swept, Money::ZERO);
+    }
+
+    #[test]
+    fn test_overspending_borrows_what_the_source_has() {
+        let mut envelopes = funded(&[
+            (Category::Food, "2024-03", "100"),
+            (Category::Shopping, "2024-03", "50"),
+        ]);
+        configure(
+            &mut envelopes,
+            Category::Food,
+            None,
+            Some(Some(Category::Shopping)),
+        )
+        .unwrap();
+        let transactions = [
+            transaction(1, "03/10/2024", "180", "Food"),
+            transaction(2, "03/11/2024", "20", "Shopping"),
+        ];
+
+        let rows = month_rows(&envelopes, &transactions, month("2024-03"));
+
+        // Shopping only has 30 left to lend
+        assert_eq!(rows[0].borrowed, money("30"));
+        assert_eq!(rows[0].balance, money("-50"));
+        assert_eq!(rows[1].borrowed, money("-30"));
+        assert_eq!(rows[1].balance, Money::ZERO);
+    }
+
+    #[test]
+    fn test_spending_before_first_funding_is_ignored() {
+        let envelopes = funded(&[(Category::Food, "2024-04", "100")]);
+        let transactions = [transaction(1, "03/10/2024", "80", "Food")];
+
+        let march = month_rows(&envelopes, &transactions, month("2024-03"));
+        let april = month_rows(&envelopes, &transactions, month("2024-04"));
+
+        assert_eq!(march[0].spent, Money::ZERO);
+        assert_eq!(april[0].carry_in, Money::ZERO);
+        assert_eq!(april[0].balance, money("100"));
+    }
+}
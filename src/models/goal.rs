@@ -0,0 +1,391 @@
+use super::account::{self, Account};
+use super::category::Category;
+use super::money::Money;
+use super::transaction::Transaction;
+use crate::utils::date_utils::YearMonth;
+use chrono::NaiveDate;
+use prettytable::{row, Cell, Row, Table};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+#[derive(Debug, thiserror::Error)]
+pub enum GoalErr {
+    #[error("No goal named \"{0}\"")]
+    NotFound(String),
+    #[error("A goal named \"{0}\" already exists")]
+    AlreadyExists(String),
+    #[error("A goal's target must be more than zero")]
+    NonPositiveTarget,
+    #[error("A goal's target date must be after today")]
+    DateInPast,
+    #[error("A contribution must be more than zero")]
+    NonPositiveContribution,
+    #[error("A goal needs a name")]
+    EmptyName,
+}
+
+/// Where money saved toward a goal is tracked, besides manual
+/// contributions.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum GoalLink {
+    /// The whole balance of a dedicated account counts as saved
+    Account(String),
+    /// Transactions in this category since the goal started count as saved
+    Category(Category),
+}
+
+impl fmt::Display for GoalLink {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Account(name) => write!(f, "Account: {}", name),
+            Self::Category(category) => write!(f, "Category: {}", category),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Contribution {
+    pub date: NaiveDate,
+    pub amount: Money,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Goal {
+    pub name: String,
+    pub target: Money,
+    pub target_date: NaiveDate,
+    pub start: NaiveDate,
+    #[serde(default)]
+    pub link: Option<GoalLink>,
+    #[serde(default)]
+    pub contributions: Vec<Contribution>,
+}
+
+impl Goal {
+    pub fn new(
+        name: &str,
+        target: Money,
+        target_date: NaiveDate,
+        link: Option<GoalLink>,
+        today: NaiveDate,
+    ) -> Result<Self, GoalErr> {
+        if name.trim().is_empty() {
+            return Err(GoalErr::EmptyName);
+        }
+        if target <= Money::ZERO {
+            return Err(GoalErr::NonPositiveTarget);
+        }
+        if target_date <= today {
+            return Err(GoalErr::DateInPast);
+        }
+        Ok(Goal {
+            name: name.trim().to_string(),
+            target,
+            target_date,
+            start: today,
+            link,
+            contributions: Vec::new(),
+        })
+    }
+
+    /// Manual contributions plus whatever the linked account or category
+    /// holds.
+    pub fn saved(&self, accounts: &[Account], transactions: &[Transaction]) -> Money {
+        let contributed: Money = self.contributions.iter().map(|c| c.amount).sum();
+        let linked = match &self.link {
+            Some(GoalLink::Account(name)) => account::find(accounts, name)
+                .map(|a| account::balance(a, transactions))
+                .unwrap_or(Money::ZERO),
+            Some(GoalLink::Category(category)) => transactions
+                .iter()
+                .filter(|t| t.date() >= self.start)
+                .flat_map(|t| t.category_lines())
+                .filter(|(c, _)| c.same(category))
+                .map(|(_, amount)| amount)
+                .sum(),
+            None => Money::ZERO,
+        };
+        contributed + linked
+    }
+
+    pub fn progress(
+        &self,
+        accounts: &[Account],
+        transactions: &[Transaction],
+        today: NaiveDate,
+    ) -> GoalProgress {
+        let saved = self.saved(accounts, transactions);
+        let remaining = (self.target - saved).max(Money::ZERO);
+
+        let months_left = YearMonth::of(today).months_until(YearMonth::of(self.target_date));
+        let monthly_needed = if remaining == Money::ZERO {
+            Some(Money::ZERO)
+        } else if today > self.target_date {
+            None
+        } else {
+            Some(remaining.mul_ratio(1, months_left.max(1) as i64))
+        };
+
+        // where a steady saver would be by today
+        let total_days = (self.target_date - self.start).num_days().max(1);
+        let elapsed = (today - self.start).num_days().clamp(0, total_days);
+        let expected = self.target.mul_ratio(elapsed, total_days);
+
+        GoalProgress {
+            saved,
+            remaining,
+            monthly_needed,
+            expected,
+        }
+    }
+}
+
+/// How a goal stands on a given day.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GoalProgress {
+    pub saved: Money,
+    pub remaining: Money,
+    /// Monthly amount needed to reach the target on time, or `None` once
+    /// the target date has passed
+    pub monthly_needed: Option<Money>,
+    /// What would be saved by now on a straight line from start to target
+    pub expected: Money,
+}
+
+impl GoalProgress {
+    /// Positive when ahead of the straight-line schedule, negative when
+    /// behind.
+    pub fn ahead_by(&self) -> Money {
+        self.saved - self.expected
+    }
+
+    pub fn status(&self) -> String {
+        let ahead = self.ahead_by();
+        if self.remaining == Money::ZERO {
+            "Reached".to_string()
+        } else if self.monthly_needed.is_none() {
+            "Overdue".to_string()
+        } else if ahead.is_negative() {
+            format!("Behind by ${}", -ahead)
+        } else if ahead == Money::ZERO {
+            "On track".to_string()
+        } else {
+            format!("Ahead by ${}", ahead)
+        }
+    }
+}
+
+fn find_mut<'a>(goals: &'a mut [Goal], name: &str) -> Result<&'a mut Goal, GoalErr> {
+    goals
+        .iter_mut()
+        .find(|g| g.name.eq_ignore_ascii_case(name.trim()))
+        .ok_or_else(|| GoalErr::NotFound(name.trim().to_string()))
+}
+
+pub fn add(goals: &mut Vec<Goal>, goal: Goal) -> Result<(), GoalErr> {
+    if goals
+        .iter()
+        .any(|g| g.name.eq_ignore_ascii_case(&goal.name))
+    {
+        return Err(GoalErr::AlreadyExists(goal.name));
+    }
+    goals.push(goal);
+    Ok(())
+}
+
+pub fn contribute(
+    goals: &mut [Goal],
+    name: &str,
+    amount: Money,
+    date: NaiveDate,
+) -> Result<(), GoalErr> {
+    if amount <= Money::ZERO {
+        return Err(GoalErr::NonPositiveContribution);
+    }
+    let goal = find_mut(goals, name)?;
+    goal.contributions.push(Contribution { date, amount });
+    Ok(())
+}
+
+pub fn print_goals(
+    goals: &[Goal],
+    accounts: &[Account],
+    transactions: &[Transaction],
+    today: NaiveDate,
+) {
+    let mut table = Table::new();
+
+    table.add_row(row![
+        "Name",
+        "Target",
+        "Saved",
+        "Progress",
+        "Target Date",
+        "Per Month",
+        "Status",
+        "Linked To"
+    ]);
+
+    for goal in goals {
+        let progress = goal.progress(accounts, transactions, today);
+        let percent = progress
+            .saved
+            .percent_of(goal.target)
+            .map(|p| format!("{:.0}%", p))
+            .unwrap_or_else(|| "-".to_string());
+        let monthly = progress
+            .monthly_needed
+            .map(|m| format!("${}", m))
+            .unwrap_or_else(|| "-".to_string());
+        let status = progress.status();
+        let status_cell = if progress.ahead_by().is_negative() && progress.remaining > Money::ZERO {
+            Cell::new(&status).style_spec("Fr")
+        } else {
+            Cell::new(&status)
+        };
+
+        table.add_row(Row::new(vec![
+            Cell::new(&goal.name),
+            Cell::new(&format!("${}", goal.target)),
+            Cell::new(&format!("${}", progress.saved)),
+            Cell::new(&percent),
+            Cell::new(&goal.target_date.format("%m/%d/%Y").to_string()),
+            Cell::new(&monthly),
+            status_cell,
+            Cell::new(
+                &goal
+                    .link
+                    .as_ref()
+                    .map(|l| l.to_string())
+                    .unwrap_or_default(),
+            ),
+        ]));
+    }
+    table.printstd();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::account::AccountKind;
+    use crate::test_util::{day, money, transaction};
+
+    fn trip() -> Goal {
+        Goal::new(
+            "Trip",
+            money("1200"),
+            day(2025, 1, 1),
+            None,
+            day(2024, 1, 1),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_new_validates_target_and_date() {
+        let today = day(2024, 1, 1);
+        assert!(matches!(
+            Goal::new("Car", Money::ZERO, day(2025, 1, 1), None, today),
+            Err(GoalErr::NonPositiveTarget)
+        ));
+        assert!(matches!(
+            Goal::new("Car", money("100"), today, None, today),
+            Err(GoalErr::DateInPast)
+        ));
+        assert!(matches!(
+            Goal::new(" ", money("100"), day(2025, 1, 1), None, today),
+            Err(GoalErr::EmptyName)
+        ));
+    }
+
+    #[test]
+    fn test_add_rejects_duplicate_names() {
+        let mut goals = vec![trip()];
+        assert!(matches!(
+            add(&mut goals, trip()),
+            Err(GoalErr::AlreadyExists(_))
+        ));
+    }
+
+    #[test]
+    fn test_contribute() {
+        let mut goals = vec![trip()];
+        contribute(&mut goals, "trip", money("100"), day(2024, 2, 1)).unwrap();
+
+        assert_eq!(goals[0].saved(&[], &[]), money("100"));
+        assert!(matches!(
+            contribute(&mut goals, "Car", money("100"), day(2024, 2, 1)),
+            Err(GoalErr::NotFound(_))
+        ));
+        assert!(matches!(
+            contribute(&mut goals, "Trip", Money::ZERO, day(2024, 2, 1)),
+            Err(GoalErr::NonPositiveContribution)
+        ));
+    }
+
+    #[test]
+    fn test_progress_behind_schedule() {
+        let mut goals = vec![trip()];
+        contribute(&mut goals, "Trip", money("300"), day(2024, 2, 1)).unwrap();
+
+        // halfway through the year with a quarter saved
+        let progress = goals[0].progress(&[], &[], day(2024, 7, 2));
+
+        assert_eq!(progress.remaining, money("900"));
+        assert_eq!(progress.monthly_needed, Some(money("150")));
+        assert!(progress.ahead_by().is_negative());
+        assert!(progress.status().starts_with("Behind"));
+    }
+
+    #[test]
+    fn test_progress_ahead_and_reached() {
+        let mut goals = vec![trip()];
+        contribute(&mut goals, "Trip", money("1000"), day(2024, 2, 1)).unwrap();
+        let progress = goals[0].progress(&[], &[], day(2024, 3, 1));
+        assert!(progress.status().starts_with("Ahead"));
+
+        contribute(&mut goals, "Trip", money("200"), day(2024, 3, 1)).unwrap();
+        let progress = goals[0].progress(&[], &[], day(2024, 3, 1));
+        assert_eq!(progress.monthly_needed, Some(Money::ZERO));
+        assert_eq!(progress.status(), "Reached");
+    }
+
+    #[test]
+    fn test_progress_overdue() {
+        let goal = trip();
+        let progress = goal.progress(&[], &[], day(2025, 2, 1));
+        assert_eq!(progress.monthly_needed, None);
+        assert_eq!(progress.status(), "Overdue");
+    }
+
+    #[test]
+    fn test_saved_from_linked_account_and_category() {
+        let accounts =
+            [Account::new("Savings", AccountKind::Savings, money("500"), "USD").unwrap()];
+        let transactions = [
+            transaction(1, "12/15/2023", "50", "expense:Car Fund"),
+            transaction(2, "02/15/2024", "75", "expense:Car Fund"),
+        ];
+
+        let by_account = Goal::new(
+            "Emergency",
+            money("3000"),
+            day(2025, 1, 1),
+            Some(GoalLink::Account("savings".to_string())),
+            day(2024, 1, 1),
+        )
+        .unwrap();
+        assert_eq!(by_account.saved(&accounts, &transactions), money("500"));
+
+        let by_category = Goal::new(
+            "Car",
+            money("3000"),
+            day(2025, 1, 1),
+            Some(GoalLink::Category("expense:car fund".parse().unwrap())),
+            day(2024, 1, 1),
+        )
+        .unwrap();
+        // the December payment predates the goal
+        assert_eq!(by_category.saved(&accounts, &transactions), money("75"));
+    }
+}
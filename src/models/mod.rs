@@ -1,3 +1,15 @@
+pub mod account;
 pub mod budget;
 pub mod category;
+pub mod debt;
+pub mod envelope;
+pub mod goal;
+pub mod money;
+pub mod networth;
+pub mod pool;
+pub mod query;
+pub mod rate;
+pub mod recurring;
+pub mod sinking_fund;
 pub mod transaction;
+pub mod trash;
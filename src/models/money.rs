@@ -0,0 +1,316 @@
+use super::transaction::TransactionErr;
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+use std::iter::Sum;
+use std::ops::{Add, AddAssign, Neg, Sub, SubAssign};
+
+/// An exact amount of money, stored as a whole number of cents.
+///
+/// Parsed from and written as plain decimal text ("12.50"), so amounts never
+/// pick up floating point error. At most two decimal places are accepted.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Money(i64);
+
+impl Money {
+    pub const ZERO: Money = Money(0);
+
+    pub fn from_cents(cents: i64) -> Self {
+        Money(cents)
+    }
+
+    pub fn cents(self) -> i64 {
+        self.0
+    }
+
+    pub fn abs(self) -> Self {
+        Money(self.0.abs())
+    }
+
+    pub fn is_negative(self) -> bool {
+        self.0 < 0
+    }
+
+    /// `self + rhs`, or `None` if the result doesn't fit.
+    pub fn checked_add(self, rhs: Money) -> Option<Money> {
+        self.0.checked_add(rhs.0).map(Money)
+    }
+
+    /// `self - rhs`, or `None` if the result doesn't fit.
+    pub fn checked_sub(self, rhs: Money) -> Option<Money> {
+        self.0.checked_sub(rhs.0).map(Money)
+    }
+
+    /// `self` as a percentage of `whole`, or `None` when `whole` is zero.
+    pub fn percent_of(self, whole: Money) -> Option<f64> {
+        if whole.0 == 0 {
+            None
+        } else {
+            Some(self.0 as f64 / whole.0 as f64 * 100.0)
+        }
+    }
+
+    /// `self * num / den`, rounded to the nearest cent (halves away from
+    /// zero). `den` must not be zero.
+    pub fn mul_ratio(self, num: i64, den: i64) -> Money {
+        let product = self.0 as i128 * num as i128;
+        let den = den as i128;
+        let (quotient, remainder) = (product / den, product % den);
+        let round = if remainder.abs() * 2 >= den.abs() {
+            product.signum() * den.signum()
+        } else {
+            0
+        };
+        Money((quotient + round) as i64)
+    }
+}
+
+impl fmt::Display for Money {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let sign = if self.0 < 0 { "-" } else { "" };
+        let cents = self.0.unsigned_abs();
+        write!(f, "{}{}.{:02}", sign, cents / 100, cents % 100)
+    }
+}
+
+impl std::str::FromStr for Money {
+    type Err = TransactionErr;
+
+    /// Accepts an optional sign, an optional `$`, digits and up to two
+    /// decimal places. Exponents, `NaN`, `inf` and extra precision are refused.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let (negative, s) = match s.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, s.strip_prefix('+').unwrap_or(s)),
+        };
+        let s = s.strip_prefix('$').unwrap_or(s);
+
+        let (whole, frac) = s.split_once('.').unwrap_or((s, ""));
+        let all_digits = |part: &str| part.bytes().all(|b| b.is_ascii_digit());
+        if (whole.is_empty() && frac.is_empty())
+            || !all_digits(whole)
+            || !all_digits(frac)
+            || frac.len() > 2
+            || (s.ends_with('.') && frac.is_empty())
+        {
+            return Err(TransactionErr::AmountParse);
+        }
+
+        let whole: i64 = if whole.is_empty() {
+            0
+        } else {
+            whole.parse().map_err(|_| TransactionErr::AmountParse)?
+        };
+        let frac: i64 = format!("{:0<2}", frac)
+            .parse()
+            .map_err(|_| TransactionErr::AmountParse)?;
+        let cents = whole
+            .checked_mul(100)
+            .and_then(|c| c.checked_add(frac))
+            .ok_or(TransactionErr::AmountParse)?;
+
+        Ok(Money(if negative { -cents } else { cents }))
+    }
+}
+
+impl Serialize for Money {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for Money {
+    /// Reads the decimal string form, and also the plain JSON numbers older
+    /// data files used. A number is converted through its shortest decimal
+    /// representation, so 25.5 becomes exactly 25.50; anything that can't be
+    /// represented in cents is an error rather than being rounded.
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct MoneyVisitor;
+
+        impl de::Visitor<'_> for MoneyVisitor {
+            type Value = Money;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(f, "a decimal amount with at most two decimal places")
+            }
+
+            fn visit_str<E: de::Error>(self, v: &str) -> Result<Money, E> {
+                v.parse()
+                    .map_err(|_| E::custom(format!("invalid amount \"{}\"", v)))
+            }
+
+            fn visit_i64<E: de::Error>(self, v: i64) -> Result<Money, E> {
+                self.visit_str(&v.to_string())
+            }
+
+            fn visit_u64<E: de::Error>(self, v: u64) -> Result<Money, E> {
+                self.visit_str(&v.to_string())
+            }
+
+            fn visit_f64<E: de::Error>(self, v: f64) -> Result<Money, E> {
+                if !v.is_finite() {
+                    return Err(E::custom(format!("invalid amount {}", v)));
+                }
+                self.visit_str(&v.to_string())
+            }
+        }
+
+        deserializer.deserialize_any(MoneyVisitor)
+    }
+}
+
+impl Add for Money {
+    type Output = Money;
+
+    fn add(self, rhs: Money) -> Money {
+        Money(self.0 + rhs.0)
+    }
+}
+
+impl Sub for Money {
+    type Output = Money;
+
+    fn sub(self, rhs: Money) -> Money {
+        Money(self.0 - rhs.0)
+    }
+}
+
+impl Neg for Money {
+    type Output = Money;
+
+    fn neg(self) -> Money {
+        Money(-self.0)
+    }
+}
+
+impl AddAssign for Money {
+    fn add_assign(&mut self, rhs: Money) {
+        self.0 += rhs.0;
+    }
+}
+
+impl SubAssign for Money {
+    fn sub_assign(&mut self, rhs: Money) {
+        self.0 -= rhs.0;
+    }
+}
+
+impl Sum for Money {
+    fn sum<I: Iterator<Item = Money>>(iter: I) -> Money {
+        iter.fold(Money::ZERO, Add::add)
+    }
+}
+
+impl<'a> Sum<&'a Money> for Money {
+    fn sum<I: Iterator<Item = &'a Money>>(iter: I) -> Money {
+        iter.copied().sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(s: &str) -> Result<Money, TransactionErr> {
+        s.parse::<Money>()
+    }
+
+    #[test]
+    fn test_parse_valid_amounts() {
+        let cases = vec![
+            ("15", 1500),
+            ("15.5", 1550),
+            ("15.05", 1505),
+            ("0.01", 1),
+            (".5", 50),
+            ("-12.34", -1234),
+            ("+3", 300),
+            ("$100.50", 10050),
+            ("  7.00 ", 700),
+        ];
+
+        for (input, cents) in cases {
+            assert_eq!(parse(input).unwrap(), Money(cents), "input {}", input);
+        }
+    }
+
+    #[test]
+    fn test_parse_rejects_non_finite_and_scientific() {
+        for input in ["NaN", "nan", "inf", "-inf", "1e3", "1E3", "", ".", "-", "1.", "1,000"] {
+            assert!(parse(input).is_err(), "accepted {:?}", input);
+        }
+    }
+
+    #[test]
+    fn test_parse_rejects_over_precise() {
+        assert!(parse("1.234").is_err());
+        assert!(parse("0.001").is_err());
+    }
+
+    #[test]
+    fn test_parse_rejects_overflow() {
+        assert!(parse("999999999999999999999").is_err());
+    }
+
+    #[test]
+    fn test_checked_add() {
+        assert_eq!(
+            parse("1.50").unwrap().checked_add(parse("2.25").unwrap()),
+            Some(parse("3.75").unwrap())
+        );
+        assert_eq!(Money::from_cents(i64::MAX).checked_add(Money::from_cents(1)), None);
+        assert_eq!(Money::from_cents(i64::MIN).checked_sub(Money::from_cents(1)), None);
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(Money(2550).to_string(), "25.50");
+        assert_eq!(Money(5).to_string(), "0.05");
+        assert_eq!(Money(-1234).to_string(), "-12.34");
+        assert_eq!(Money::ZERO.to_string(), "0.00");
+    }
+
+    #[test]
+    fn test_percent_of() {
+        assert_eq!(Money(2500).percent_of(Money(10000)), Some(25.0));
+        assert_eq!(Money(100).percent_of(Money::ZERO), None);
+    }
+
+    #[test]
+    fn test_mul_ratio_rounds_half_away_from_zero() {
+        assert_eq!(Money(1000).mul_ratio(1, 3), Money(333));
+        assert_eq!(Money(1000).mul_ratio(2, 3), Money(667));
+        assert_eq!(Money(5).mul_ratio(1, 2), Money(3));
+        assert_eq!(Money(-5).mul_ratio(1, 2), Money(-3));
+        assert_eq!(Money(120000).mul_ratio(12, 12), Money(120000));
+    }
+
+    #[test]
+    fn test_sums_are_exact() {
+        // 0.1 + 0.2 drifts as f64 but not in cents
+        let total: Money = ["0.10", "0.20"].iter().map(|s| parse(s).unwrap()).sum();
+        assert_eq!(total, parse("0.30").unwrap());
+    }
+
+    #[test]
+    fn test_serialization_roundtrip() {
+        let amount = Money(10050);
+        let json = serde_json::to_string(&amount).unwrap();
+        assert_eq!(json, "\"100.50\"");
+        assert_eq!(serde_json::from_str::<Money>(&json).unwrap(), amount);
+    }
+
+    #[test]
+    fn test_deserialize_legacy_numbers() {
+        assert_eq!(serde_json::from_str::<Money>("25.5").unwrap(), Money(2550));
+        assert_eq!(serde_json::from_str::<Money>("100").unwrap(), Money(10000));
+        assert_eq!(serde_json::from_str::<Money>("0.1").unwrap(), Money(10));
+        assert_eq!(serde_json::from_str::<Money>("-3.25").unwrap(), Money(-325));
+    }
+
+    #[test]
+    fn test_deserialize_legacy_over_precise_is_error() {
+        assert!(serde_json::from_str::<Money>("10.005").is_err());
+        assert!(serde_json::from_str::<Money>("\"1e3\"").is_err());
+    }
+}
@@ -0,0 +1,420 @@
+use super::account::{self, Account};
+use super::money::Money;
+use super::transaction::Transaction;
+use crate::utils::date_utils::YearMonth;
+use chrono::NaiveDate;
+use prettytable::{row, Cell, Row, Table};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Holdings are valued in US dollars, so only accounts in dollars are added
+/// to them; other currencies are totalled separately.
+const HOME_CURRENCY: &str = "USD";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum HoldingKind {
+    Asset,
+    Liability,
+}
+
+impl fmt::Display for HoldingKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Asset => write!(f, "Asset"),
+            Self::Liability => write!(f, "Liability"),
+        }
+    }
+}
+
+impl std::str::FromStr for HoldingKind {
+    type Err = NetWorthErr;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_uppercase().as_str() {
+            "ASSET" => Ok(Self::Asset),
+            "LIABILITY" => Ok(Self::Liability),
+            _ => Err(NetWorthErr::KindParse),
+        }
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum NetWorthErr {
+    #[error("Invalid holding kind, expected asset or liability")]
+    KindParse,
+    #[error("No holding named \"{0}\"")]
+    NotFound(String),
+    #[error("A holding named \"{0}\" already exists")]
+    AlreadyExists(String),
+    #[error("A valuation can't be negative; record debts as liabilities")]
+    NegativeValue,
+    #[error("A holding needs a name")]
+    EmptyName,
+}
+
+/// What a holding was worth, or owed, on a date.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Valuation {
+    pub date: NaiveDate,
+    pub value: Money,
+}
+
+/// Something owned or owed outside the ledger: a house, a car, a
+/// mortgage, a brokerage account.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Holding {
+    pub name: String,
+    pub kind: HoldingKind,
+    /// Oldest first
+    pub valuations: Vec<Valuation>,
+}
+
+impl Holding {
+    pub fn new(name: &str, kind: HoldingKind) -> Result<Self, NetWorthErr> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err(NetWorthErr::EmptyName);
+        }
+        Ok(Holding {
+            name: name.to_string(),
+            kind,
+            valuations: Vec::new(),
+        })
+    }
+
+    /// The latest valuation on or before `date`.
+    pub fn value_on(&self, date: NaiveDate) -> Option<Money> {
+        self.valuations
+            .iter()
+            .rev()
+            .find(|v| v.date <= date)
+            .map(|v| v.value)
+    }
+
+    /// Record a valuation, replacing any made on the same date.
+    pub fn revalue(&mut self, date: NaiveDate, value: Money) -> Result<(), NetWorthErr> {
+        if value.is_negative() {
+            return Err(NetWorthErr::NegativeValue);
+        }
+        self.valuations.retain(|v| v.date != date);
+        self.valuations.push(Valuation { date, value });
+        self.valuations.sort_by_key(|v| v.date);
+        Ok(())
+    }
+}
+
+fn find_mut<'a>(holdings: &'a mut [Holding], name: &str) -> Result<&'a mut Holding, NetWorthErr> {
+    holdings
+        .iter_mut()
+        .find(|h| h.name.eq_ignore_ascii_case(name.trim()))
+        .ok_or_else(|| NetWorthErr::NotFound(name.trim().to_string()))
+}
+
+pub fn add(holdings: &mut Vec<Holding>, holding: Holding) -> Result<(), NetWorthErr> {
+    if holdings
+        .iter()
+        .any(|h| h.name.eq_ignore_ascii_case(&holding.name))
+    {
+        return Err(NetWorthErr::AlreadyExists(holding.name));
+    }
+    holdings.push(holding);
+    Ok(())
+}
+
+pub fn revalue(
+    holdings: &mut [Holding],
+    name: &str,
+    date: NaiveDate,
+    value: Money,
+) -> Result<(), NetWorthErr> {
+    find_mut(holdings, name)?.revalue(date, value)
+}
+
+pub fn remove(holdings: &mut Vec<Holding>, name: &str) -> Result<(), NetWorthErr> {
+    let name = find_mut(holdings, name)?.name.clone();
+    holdings.retain(|h| h.name != name);
+    Ok(())
+}
+
+/// Net worth on one date.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NetWorth {
+    pub date: NaiveDate,
+    pub assets: Money,
+    pub liabilities: Money,
+    /// Combined balance of the ledger's dollar accounts; credit cards and
+    /// loans tracked as accounts bring it down
+    pub accounts: Money,
+    /// Combined balance of the accounts in each other currency, which the
+    /// total leaves out
+    pub other_currencies: Vec<(String, Money)>,
+}
+
+impl NetWorth {
+    pub fn total(&self) -> Money {
+        self.assets + self.accounts - self.liabilities
+    }
+}
+
+pub fn net_worth_on(
+    holdings: &[Holding],
+    accounts: &[Account],
+    transactions: &[Transaction],
+    date: NaiveDate,
+) -> NetWorth {
+    let total_of = |kind: HoldingKind| {
+        holdings
+            .iter()
+            .filter(|h| h.kind == kind)
+            .filter_map(|h| h.value_on(date))
+            .sum()
+    };
+
+    let mut home = Money::ZERO;
+    let mut other_currencies: Vec<(String, Money)> = Vec::new();
+    for a in accounts {
+        let balance = account::balance_on(a, transactions, date);
+        if a.currency == HOME_CURRENCY {
+            home += balance;
+            continue;
+        }
+        match other_currencies.iter_mut().find(|(c, _)| *c == a.currency) {
+            Some((_, total)) => *total += balance,
+            None => other_currencies.push((a.currency.clone(), balance)),
+        }
+    }
+
+    NetWorth {
+        date,
+        assets: total_of(HoldingKind::Asset),
+        liabilities: total_of(HoldingKind::Liability),
+        accounts: home,
+        other_currencies,
+    }
+}
+
+/// Net worth at the end of each of the last `months` months, oldest
+/// first, with the current month measured as of `today`.
+pub fn monthly_history(
+    holdings: &[Holding],
+    accounts: &[Account],
+    transactions: &[Transaction],
+    today: NaiveDate,
+    months: u32,
+) -> Vec<(YearMonth, NetWorth)> {
+    let this_month = YearMonth::of(today);
+    (0..months as i32)
+        .rev()
+        .map(|back| {
+            let month = this_month.add_months(-back);
+            let date = month.last_day().min(today);
+            (month, net_worth_on(holdings, accounts, transactions, date))
+        })
+        .collect()
+}
+
+pub fn print_holdings(holdings: &[Holding], today: NaiveDate) {
+    let mut table = Table::new();
+
+    table.add_row(row!["Name", "Kind", "Value", "As Of"]);
+
+    for holding in holdings {
+        let latest = holding.valuations.iter().rfind(|v| v.date <= today);
+        table.add_row(Row::new(vec![
+            Cell::new(&holding.name),
+            Cell::new(&holding.kind.to_string()),
+            Cell::new(
+                &latest
+                    .map(|v| format!("${}", v.value))
+                    .unwrap_or_else(|| "-".to_string()),
+            ),
+            Cell::new(&latest.map(|v| v.date.to_string()).unwrap_or_default()),
+        ]));
+    }
+    table.printstd();
+}
+
+pub fn print_net_worth(
+    current: &NetWorth,
+    holdings: &[Holding],
+    accounts: &[Account],
+    transactions: &[Transaction],
+    history: &[(YearMonth, NetWorth)],
+) {
+    let mut items = Table::new();
+
+    items.add_row(row!["Name", "Type", "Value"]);
+
+    for holding in holdings {
+        let Some(value) = holding.value_on(current.date) else {
+            continue;
+        };
+        let shown = match holding.kind {
+            HoldingKind::Asset => value,
+            HoldingKind::Liability => -value,
+        };
+        items.add_row(Row::new(vec![
+            Cell::new(&holding.name),
+            Cell::new(&holding.kind.to_string()),
+            Cell::new(&format!("${}", shown)),
+        ]));
+    }
+    for a in accounts {
+        items.add_row(Row::new(vec![
+            Cell::new(&a.name),
+            Cell::new(&format!("{} account", a.kind)),
+            Cell::new(&a.format_amount(account::balance_on(a, transactions, current.date))),
+        ]));
+    }
+
+    let mut months = Table::new();
+
+    months.add_row(row![
+        "Month",
+        "Assets",
+        "Accounts",
+        "Liabilities",
+        "Net Worth"
+    ]);
+
+    for (month, worth) in history {
+        let total = format!("${}", worth.total());
+        let total_cell = if worth.total().is_negative() {
+            Cell::new(&total).style_spec("Fr")
+        } else {
+            Cell::new(&total)
+        };
+        months.add_row(Row::new(vec![
+            Cell::new(&month.to_string()),
+            Cell::new(&format!("${}", worth.assets)),
+            Cell::new(&format!("${}", worth.accounts)),
+            Cell::new(&format!("${}", worth.liabilities)),
+            total_cell,
+        ]));
+    }
+
+    items.printstd();
+    println!("Net worth on {}: ${}", current.date, current.total());
+    for (currency, total) in &current.other_currencies {
+        println!(
+            "Accounts in {} hold {} {}, which the net worth leaves out",
+            currency, total, currency
+        );
+    }
+    months.printstd();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::account::AccountKind;
+    use crate::test_util::{day, money, transaction};
+
+    fn holdings() -> Vec<Holding> {
+        let mut house = Holding::new("House", HoldingKind::Asset).unwrap();
+        house.revalue(day(2024, 1, 1), money("400000")).unwrap();
+        house.revalue(day(2024, 3, 15), money("410000")).unwrap();
+        let mut mortgage = Holding::new("Mortgage", HoldingKind::Liability).unwrap();
+        mortgage.revalue(day(2024, 1, 1), money("300000")).unwrap();
+        mortgage.revalue(day(2024, 2, 1), money("299000")).unwrap();
+        vec![house, mortgage]
+    }
+
+    #[test]
+    fn test_holding_kind_parsing() {
+        assert_eq!("asset".parse::<HoldingKind>().unwrap(), HoldingKind::Asset);
+        assert_eq!(
+            "LIABILITY".parse::<HoldingKind>().unwrap(),
+            HoldingKind::Liability
+        );
+        assert!("house".parse::<HoldingKind>().is_err());
+    }
+
+    #[test]
+    fn test_value_on_uses_latest_snapshot() {
+        let house = &holdings()[0];
+        assert_eq!(house.value_on(day(2023, 12, 31)), None);
+        assert_eq!(house.value_on(day(2024, 3, 14)), Some(money("400000")));
+        assert_eq!(house.value_on(day(2024, 3, 15)), Some(money("410000")));
+    }
+
+    #[test]
+    fn test_revalue_replaces_same_date() {
+        let mut holdings = holdings();
+        revalue(&mut holdings, "house", day(2024, 3, 15), money("405000")).unwrap();
+
+        assert_eq!(holdings[0].valuations.len(), 2);
+        assert_eq!(holdings[0].value_on(day(2024, 4, 1)), Some(money("405000")));
+        assert!(matches!(
+            revalue(&mut holdings, "House", day(2024, 4, 1), money("-1")),
+            Err(NetWorthErr::NegativeValue)
+        ));
+        assert!(matches!(
+            revalue(&mut holdings, "Boat", day(2024, 4, 1), money("1")),
+            Err(NetWorthErr::NotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_add_and_remove() {
+        let mut holdings = holdings();
+        assert!(matches!(
+            add(
+                &mut holdings,
+                Holding::new("house", HoldingKind::Asset).unwrap()
+            ),
+            Err(NetWorthErr::AlreadyExists(_))
+        ));
+        assert!(matches!(
+            Holding::new(" ", HoldingKind::Asset),
+            Err(NetWorthErr::EmptyName)
+        ));
+        remove(&mut holdings, "MORTGAGE").unwrap();
+        assert_eq!(holdings.len(), 1);
+    }
+
+    #[test]
+    fn test_net_worth_includes_accounts() {
+        let accounts = [
+            Account::new("Checking", AccountKind::Checking, money("1000"), "USD").unwrap(),
+            Account::new("Visa", AccountKind::Credit, Money::ZERO, "USD").unwrap(),
+        ];
+        let transactions =
+            [transaction(1, "02/10/2024", "250", "Shopping")
+                .with_account(Some("Visa".to_string()))];
+
+        let worth = net_worth_on(&holdings(), &accounts, &transactions, day(2024, 2, 29));
+
+        assert_eq!(worth.assets, money("400000"));
+        assert_eq!(worth.liabilities, money("299000"));
+        assert_eq!(worth.accounts, money("750"));
+        assert_eq!(worth.total(), money("101750"));
+    }
+
+    #[test]
+    fn test_net_worth_totals_other_currencies_apart() {
+        let accounts = [
+            Account::new("Checking", AccountKind::Checking, money("1000"), "USD").unwrap(),
+            Account::new("Euro", AccountKind::Savings, money("500"), "EUR").unwrap(),
+            Account::new("Cash", AccountKind::Cash, money("20"), "eur").unwrap(),
+        ];
+
+        let worth = net_worth_on(&[], &accounts, &[], day(2024, 2, 29));
+
+        assert_eq!(worth.accounts, money("1000"));
+        assert_eq!(worth.total(), money("1000"));
+        assert_eq!(worth.other_currencies, [("EUR".to_string(), money("520"))]);
+    }
+
+    #[test]
+    fn test_monthly_history() {
+        let history = monthly_history(&holdings(), &[], &[], day(2024, 3, 20), 3);
+
+        let months: Vec<String> = history.iter().map(|(m, _)| m.to_string()).collect();
+        assert_eq!(months, ["2024-01", "2024-02", "2024-03"]);
+        assert_eq!(history[0].1.total(), money("100000"));
+        assert_eq!(history[1].1.total(), money("101000"));
+        assert_eq!(history[2].1.date, day(2024, 3, 20));
+        assert_eq!(history[2].1.total(), money("111000"));
+    }
+}
@@ -0,0 +1,268 @@
+use super::envelope::{self, Envelope};
+use super::money::Money;
+use super::transaction::Transaction;
+use crate::utils::date_utils::YearMonth;
+use chrono::NaiveDate;
+use prettytable::{row, Cell, Row, Table};
+
+#[derive(Debug, thiserror::Error)]
+pub enum PoolErr {
+    #[error("{0} is closed")]
+    MonthClosed(YearMonth),
+    #[error("{0} is already closed")]
+    AlreadyClosed(YearMonth),
+    #[error("{month} has ${over} more assigned than available; use --force to close it anyway")]
+    OverAssigned { month: YearMonth, over: Money },
+}
+
+/// The "to be assigned" pool for one month in zero-based budgeting.
+///
+/// Income feeds the pool and funding envelopes draws it down. Whatever is
+/// left carries into the next month.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PoolSummary {
+    pub month: YearMonth,
+    pub carried_in: Money,
+    pub income: Money,
+    /// Surplus swept back from envelopes at the start of the month
+    pub returned: Money,
+    pub assigned: Money,
+}
+
+impl PoolSummary {
+    pub fn available(&self) -> Money {
+        self.carried_in + self.income + self.returned - self.assigned
+    }
+}
+
+pub fn summary(
+    envelopes: &[Envelope],
+    transactions: &[Transaction],
+    month: YearMonth,
+) -> PoolSummary {
+    let mut income_before = Money::ZERO;
+    let mut income = Money::ZERO;
+    for t in transactions {
+        let earned: Money = t
+            .category_lines()
+            .into_iter()
+            .filter(|(c, _)| c.is_income())
+            .map(|(_, amount)| amount)
+            .sum();
+        let earned_in = YearMonth::of(t.date());
+        if earned_in < month {
+            income_before += earned;
+        } else if earned_in == month {
+            income += earned;
+        }
+    }
+
+    let mut assigned_before = Money::ZERO;
+    let mut assigned = Money::ZERO;
+    for funding in envelopes.iter().flat_map(|e| &e.funding) {
+        if funding.month < month {
+            assigned_before += funding.amount;
+        } else if funding.month == month {
+            assigned += funding.amount;
+        }
+    }
+
+    let mut returned_before = Money::ZERO;
+    let mut returned = Money::ZERO;
+    for (m, rows) in envelope::history(envelopes, transactions, month) {
+        let swept: Money = rows.iter().map(|r| r.swept).sum();
+        if m < month {
+            returned_before += swept;
+        } else if m == month {
+            returned += swept;
+        }
+    }
+
+    PoolSummary {
+        month,
+        carried_in: income_before + returned_before - assigned_before,
+        income,
+        returned,
+        assigned,
+    }
+}
+
+/// Refuse changes to a month that has been closed.
+pub fn ensure_open(closed: &[YearMonth], month: YearMonth) -> Result<(), PoolErr> {
+    if closed.contains(&month) {
+        return Err(PoolErr::MonthClosed(month));
+    }
+    Ok(())
+}
+
+/// Refuse changes to transactions dated in a closed month.
+pub fn ensure_dates_open(
+    closed: &[YearMonth],
+    dates: impl IntoIterator<Item = NaiveDate>,
+) -> Result<(), PoolErr> {
+    dates
+        .into_iter()
+        .try_for_each(|date| ensure_open(closed, YearMonth::of(date)))
+}
+
+/// Refuse a change that reworks every month from `from` on, such as an
+/// envelope's rollover or borrowing, when one of those months is closed.
+pub fn ensure_open_from(closed: &[YearMonth], from: YearMonth) -> Result<(), PoolErr> {
+    match closed.iter().filter(|&&m| m >= from).min() {
+        Some(&month) => Err(PoolErr::MonthClosed(month)),
+        None => Ok(()),
+    }
+}
+
+/// Close a month so its assignments can't change. A month with more
+/// assigned than available is only closed when `force` is set.
+pub fn close_month(
+    closed: &mut Vec<YearMonth>,
+    summary: &PoolSummary,
+    force: bool,
+) -> Result<(), PoolErr> {
+    if closed.contains(&summary.month) {
+        return Err(PoolErr::AlreadyClosed(summary.month));
+    }
+    let available = summary.available();
+    if available.is_negative() && !force {
+        return Err(PoolErr::OverAssigned {
+            month: summary.month,
+            over: -available,
+        });
+    }
+    closed.push(summary.month);
+    closed.sort();
+    Ok(())
+}
+
+pub fn print_summary(summary: &PoolSummary, closed: bool) {
+    let mut table = Table::new();
+
+    table.add_row(row![
+        "Carried In",
+        "Income",
+        "Returned",
+        "Assigned",
+        "To Be Assigned"
+    ]);
+
+    let available = format!("${}", summary.available());
+    let available_cell = if summary.available().is_negative() {
+        Cell::new(&available).style_spec("Fr")
+    } else {
+        Cell::new(&available)
+    };
+    table.add_row(Row::new(vec![
+        Cell::new(&format!("${}", summary.carried_in)),
+        Cell::new(&format!("${}", summary.income)),
+        Cell::new(&format!("${}", summary.returned)),
+        Cell::new(&format!("${}", summary.assigned)),
+        available_cell,
+    ]));
+
+    if closed {
+        println!("Pool for {} (closed)", summary.month);
+    } else {
+        println!("Pool for {}", summary.month);
+    }
+    table.printstd();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::category::Category;
+    use crate::models::envelope::Rollover;
+    use crate::test_util::{day, funded, ledger, money, month};
+
+    fn transactions() -> Vec<Transaction> {
+        ledger(&[
+            ("02/28/2024", "1000", "Salary"),
+            ("03/01/2024", "500", "Freelance"),
+            ("03/05/2024", "70", "Shopping"),
+        ])
+    }
+
+    #[test]
+    fn test_summary_carries_unassigned_income() {
+        let envelopes = funded(&[
+            (Category::Food, "2024-02", "600"),
+            (Category::Food, "2024-03", "300"),
+        ]);
+
+        let pool = summary(&envelopes, &transactions(), month("2024-03"));
+
+        assert_eq!(pool.carried_in, money("400"));
+        assert_eq!(pool.income, money("500"));
+        assert_eq!(pool.assigned, money("300"));
+        assert_eq!(pool.available(), money("600"));
+    }
+
+    #[test]
+    fn test_summary_counts_swept_surplus() {
+        let mut envelopes = funded(&[(Category::Shopping, "2024-03", "100")]);
+        envelope::configure(
+            &mut envelopes,
+            Category::Shopping,
+            Some(Rollover::Sweep),
+            None,
+        )
+        .unwrap();
+
+        let pool = summary(&envelopes, &transactions(), month("2024-04"));
+
+        // 1500 earned, 100 assigned, 30 unspent swept back
+        assert_eq!(pool.carried_in, money("1400"));
+        assert_eq!(pool.returned, money("30"));
+        assert_eq!(pool.available(), money("1430"));
+    }
+
+    #[test]
+    fn test_close_month_refuses_negative_pool_unless_forced() {
+        let envelopes = funded(&[(Category::Food, "2024-03", "2000")]);
+        let pool = summary(&envelopes, &transactions(), month("2024-03"));
+        let mut closed = Vec::new();
+
+        let result = close_month(&mut closed, &pool, false);
+        assert!(matches!(result, Err(PoolErr::OverAssigned { over, .. }) if over == money("500")));
+        assert!(closed.is_empty());
+
+        close_month(&mut closed, &pool, true).unwrap();
+        assert_eq!(closed, vec![month("2024-03")]);
+        assert!(matches!(
+            close_month(&mut closed, &pool, true),
+            Err(PoolErr::AlreadyClosed(_))
+        ));
+    }
+
+    #[test]
+    fn test_ensure_open() {
+        let closed = [month("2024-03")];
+        assert!(ensure_open(&closed, month("2024-04")).is_ok());
+        assert!(matches!(
+            ensure_open(&closed, month("2024-03")),
+            Err(PoolErr::MonthClosed(_))
+        ));
+    }
+
+    #[test]
+    fn test_ensure_dates_open() {
+        let closed = [month("2024-03")];
+        assert!(ensure_dates_open(&closed, [day(2024, 2, 29), day(2024, 4, 1)]).is_ok());
+        assert!(matches!(
+            ensure_dates_open(&closed, [day(2024, 4, 1), day(2024, 3, 31)]),
+            Err(PoolErr::MonthClosed(m)) if m == month("2024-03")
+        ));
+    }
+
+    #[test]
+    fn test_ensure_open_from() {
+        let closed = [month("2024-05"), month("2024-03")];
+        assert!(ensure_open_from(&closed, month("2024-06")).is_ok());
+        assert!(matches!(
+            ensure_open_from(&closed, month("2024-01")),
+            Err(PoolErr::MonthClosed(m)) if m == month("2024-03")
+        ));
+    }
+}
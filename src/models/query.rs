@@ -0,0 +1,322 @@
+use super::category::Category;
+use super::money::Money;
+use super::transaction::Transaction;
+use crate::utils::date_utils::YearMonth;
+use chrono::NaiveDate;
+
+/// A set of conditions a transaction must all meet. Unset fields match
+/// everything, so `TransactionQuery::default()` selects the whole ledger.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TransactionQuery {
+    /// Inclusive start date.
+    pub from: Option<NaiveDate>,
+    /// Inclusive end date.
+    pub to: Option<NaiveDate>,
+    /// A category name, or `type:name` for custom categories.
+    pub category: Option<String>,
+    /// "Income", "Expense" or "Transfer", compared against
+    /// [`Category::category_type`].
+    pub kind: Option<String>,
+    pub min: Option<Money>,
+    pub max: Option<Money>,
+    /// Case-insensitive substring of the note.
+    pub note_contains: Option<String>,
+    pub account: Option<String>,
+    /// Leave out both legs of transfers between accounts.
+    pub exclude_transfers: bool,
+}
+
+impl TransactionQuery {
+    /// Everything dated from `from` to `to`, inclusive.
+    pub fn between(from: NaiveDate, to: NaiveDate) -> Self {
+        TransactionQuery {
+            from: Some(from),
+            to: Some(to),
+            ..Default::default()
+        }
+    }
+
+    pub fn in_month(month: YearMonth) -> Self {
+        Self::between(month.first_day(), month.last_day())
+    }
+
+    /// Narrow the query to one category, matched the same way as a
+    /// `type:name` filter typed on the command line.
+    pub fn with_category(mut self, category: &Category) -> Self {
+        self.category = Some(format!("{}:{}", category.category_type(), category));
+        self
+    }
+
+    pub fn with_kind(mut self, kind: &str) -> Self {
+        self.kind = Some(kind.to_string());
+        self
+    }
+
+    pub fn without_transfers(mut self) -> Self {
+        self.exclude_transfers = true;
+        self
+    }
+
+    pub fn matches(&self, transaction: &Transaction) -> bool {
+        let date = transaction.date();
+        let amount = transaction.amount();
+        let category = transaction.category();
+        let lines = transaction.category_lines();
+
+        !(self.exclude_transfers && category.is_transfer())
+            && self.from.is_none_or(|from| date >= from)
+            && self.to.is_none_or(|to| date <= to)
+            && self
+                .category
+                .as_deref()
+                .is_none_or(|filter| lines.iter().any(|(c, _)| category_matches(filter, c)))
+            && self
+                .kind
+                .as_deref()
+                .is_none_or(|kind| category.category_type().eq_ignore_ascii_case(kind))
+            && self.min.is_none_or(|min| amount >= min)
+            && self.max.is_none_or(|max| amount <= max)
+            && self.note_contains.as_deref().is_none_or(|needle| {
+                transaction
+                    .note()
+                    .is_some_and(|note| note.to_lowercase().contains(&needle.to_lowercase()))
+            })
+            && self.account.as_deref().is_none_or(|account| {
+                transaction
+                    .account()
+                    .is_some_and(|name| name.eq_ignore_ascii_case(account.trim()))
+            })
+    }
+
+    /// The matching transactions, in ledger order.
+    pub fn apply<'a>(&self, transactions: &'a [Transaction]) -> Vec<&'a Transaction> {
+        transactions.iter().filter(|t| self.matches(t)).collect()
+    }
+}
+
+/// Fields the list view can be ordered by.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SortKey {
+    Date,
+    Amount,
+    Category,
+    Id,
+}
+
+/// Stable sort, so ties keep their ledger order.
+pub fn sort_transactions(transactions: &mut [&Transaction], key: SortKey, descending: bool) {
+    transactions.sort_by(|a, b| {
+        let ordering = match key {
+            SortKey::Date => a.date().cmp(&b.date()),
+            SortKey::Amount => a.amount().cmp(&b.amount()),
+            SortKey::Category => a
+                .category_label()
+                .to_lowercase()
+                .cmp(&b.category_label().to_lowercase()),
+            SortKey::Id => a.id().cmp(&b.id()),
+        };
+        if descending {
+            ordering.reverse()
+        } else {
+            ordering
+        }
+    });
+}
+
+fn category_matches(filter: &str, category: &Category) -> bool {
+    match filter.split_once(':') {
+        Some((kind, name)) => {
+            category.category_type().eq_ignore_ascii_case(kind.trim())
+                && category.to_string().eq_ignore_ascii_case(name.trim())
+        }
+        None => category.to_string().eq_ignore_ascii_case(filter.trim()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util;
+
+    fn ledger() -> Vec<Transaction> {
+        let mut transactions = test_util::ledger(&[
+            ("03/02/2024", "25.50", "Food"),
+            ("03/15/2024", "2000.00", "Salary"),
+            ("03/20/2024", "60.00", "Food"),
+            ("04/01/2024", "12.00", "Food"),
+            ("03/21/2024", "40.00", "expense:Pet Care"),
+        ]);
+        let notes = [(0, "Groceries"), (2, "Dinner out")];
+        for (i, note) in notes {
+            transactions[i]
+                .edit(None, None, None, Some(note.to_string()))
+                .unwrap();
+        }
+        transactions
+    }
+
+    fn ids(found: Vec<&Transaction>) -> Vec<u32> {
+        found.iter().map(|t| t.id()).collect()
+    }
+
+    #[test]
+    fn test_default_matches_everything() {
+        let transactions = ledger();
+        assert_eq!(TransactionQuery::default().apply(&transactions).len(), 5);
+    }
+
+    #[test]
+    fn test_category_in_date_range() {
+        let transactions = ledger();
+        let query = TransactionQuery {
+            from: NaiveDate::from_ymd_opt(2024, 3, 1),
+            to: NaiveDate::from_ymd_opt(2024, 3, 31),
+            category: Some("food".to_string()),
+            ..Default::default()
+        };
+
+        assert_eq!(ids(query.apply(&transactions)), vec![1, 3]);
+    }
+
+    #[test]
+    fn test_custom_category_with_and_without_type() {
+        let transactions = ledger();
+        let by_name = TransactionQuery {
+            category: Some("pet care".to_string()),
+            ..Default::default()
+        };
+        let by_type = TransactionQuery {
+            category: Some("expense:Pet Care".to_string()),
+            ..Default::default()
+        };
+        let wrong_type = TransactionQuery {
+            category: Some("income:Pet Care".to_string()),
+            ..Default::default()
+        };
+
+        assert_eq!(ids(by_name.apply(&transactions)), vec![5]);
+        assert_eq!(ids(by_type.apply(&transactions)), vec![5]);
+        assert!(wrong_type.apply(&transactions).is_empty());
+    }
+
+    #[test]
+    fn test_kind_filter() {
+        let transactions = ledger();
+        let query = TransactionQuery {
+            kind: Some("income".to_string()),
+            ..Default::default()
+        };
+
+        assert_eq!(ids(query.apply(&transactions)), vec![2]);
+    }
+
+    #[test]
+    fn test_month_and_category_helpers() {
+        let transactions = ledger();
+        let march = TransactionQuery::in_month("2024-03".parse().unwrap());
+        assert_eq!(ids(march.clone().apply(&transactions)), vec![1, 2, 3, 5]);
+
+        let pet_care = Category::Custom {
+            income_or_expense: "Expense".to_string(),
+            name: "pet care".to_string(),
+        };
+        assert_eq!(
+            ids(march.clone().with_category(&pet_care).apply(&transactions)),
+            vec![5]
+        );
+        assert_eq!(
+            ids(march.with_category(&Category::Food).apply(&transactions)),
+            vec![1, 3]
+        );
+    }
+
+    #[test]
+    fn test_transfers_excluded_or_selected_by_kind() {
+        let (out, into) = Transaction::transfer_pair(
+            (6, 7),
+            NaiveDate::from_ymd_opt(2024, 3, 10).unwrap(),
+            "100".parse().unwrap(),
+            ("Checking", "Savings"),
+            None,
+        )
+        .unwrap();
+        let mut transactions = ledger();
+        transactions.extend([out, into]);
+
+        let without = TransactionQuery::default().without_transfers();
+        assert_eq!(ids(without.apply(&transactions)), vec![1, 2, 3, 4, 5]);
+        let only = TransactionQuery::default().with_kind("transfer");
+        assert_eq!(ids(only.apply(&transactions)), vec![6, 7]);
+    }
+
+    #[test]
+    fn test_amount_bounds_inclusive() {
+        let transactions = ledger();
+        let query = TransactionQuery {
+            min: "25.50".parse().ok(),
+            max: "60.00".parse().ok(),
+            ..Default::default()
+        };
+
+        assert_eq!(ids(query.apply(&transactions)), vec![1, 3, 5]);
+    }
+
+    #[test]
+    fn test_note_contains_is_case_insensitive() {
+        let transactions = ledger();
+        let query = TransactionQuery {
+            note_contains: Some("DINNER".to_string()),
+            ..Default::default()
+        };
+
+        assert_eq!(ids(query.apply(&transactions)), vec![3]);
+    }
+
+    #[test]
+    fn test_sort_by_amount() {
+        let transactions = ledger();
+        let mut found = TransactionQuery::default().apply(&transactions);
+
+        sort_transactions(&mut found, SortKey::Amount, false);
+        assert_eq!(ids(found.clone()), vec![4, 1, 5, 3, 2]);
+
+        sort_transactions(&mut found, SortKey::Amount, true);
+        assert_eq!(ids(found), vec![2, 3, 5, 1, 4]);
+    }
+
+    #[test]
+    fn test_sort_by_category_keeps_ledger_order_for_ties() {
+        let transactions = ledger();
+        let mut found = TransactionQuery::default().apply(&transactions);
+
+        sort_transactions(&mut found, SortKey::Category, false);
+        assert_eq!(ids(found), vec![1, 3, 4, 5, 2]);
+    }
+
+    #[test]
+    fn test_sort_by_date_desc() {
+        let transactions = ledger();
+        let mut found = TransactionQuery::default().apply(&transactions);
+
+        sort_transactions(&mut found, SortKey::Date, true);
+        assert_eq!(ids(found), vec![4, 5, 3, 2, 1]);
+    }
+
+    #[test]
+    fn test_category_matches_any_split_line() {
+        let transactions = vec![Transaction::new_split(
+            1,
+            "03/05/2024",
+            "65.50",
+            &["Food=40".to_string(), "Shopping=25.50".to_string()],
+            None,
+        )
+        .unwrap()];
+        let query = TransactionQuery {
+            category: Some("shopping".to_string()),
+            ..Default::default()
+        };
+
+        assert_eq!(ids(query.apply(&transactions)), vec![1]);
+    }
+}
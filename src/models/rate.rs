@@ -0,0 +1,115 @@
+use super::money::Money;
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+
+/// Fixed-point scale of a [`Rate`]: units per whole percent.
+const RATE_SCALE: i64 = 100_000;
+
+#[derive(Debug, thiserror::Error)]
+#[error("Invalid rate, expected a percentage such as 6.5 or 6.5%")]
+pub struct RateParseErr;
+
+/// An annual percentage rate, kept exactly to five decimal places of a
+/// percent so interest is computed in whole cents without float drift.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Rate(i64);
+
+impl Rate {
+    /// One month of interest on `balance`, rounded to the cent.
+    pub fn monthly_interest(self, balance: Money) -> Money {
+        balance.mul_ratio(self.0, 12 * 100 * RATE_SCALE)
+    }
+
+    /// One month of interest as a fraction of the balance.
+    pub fn monthly_fraction(self) -> f64 {
+        self.0 as f64 / (12 * 100 * RATE_SCALE) as f64
+    }
+}
+
+impl fmt::Display for Rate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let frac = format!("{:05}", self.0 % RATE_SCALE);
+        let frac = frac.trim_end_matches('0');
+        if frac.is_empty() {
+            write!(f, "{}%", self.0 / RATE_SCALE)
+        } else {
+            write!(f, "{}.{}%", self.0 / RATE_SCALE, frac)
+        }
+    }
+}
+
+impl std::str::FromStr for Rate {
+    type Err = RateParseErr;
+
+    /// Accepts a non-negative percentage with up to five decimal places and
+    /// an optional trailing `%`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let s = s.strip_suffix('%').unwrap_or(s).trim_end();
+
+        let (whole, frac) = s.split_once('.').unwrap_or((s, ""));
+        let all_digits = |part: &str| part.bytes().all(|b| b.is_ascii_digit());
+        if (whole.is_empty() && frac.is_empty())
+            || !all_digits(whole)
+            || !all_digits(frac)
+            || frac.len() > 5
+            || (s.ends_with('.') && frac.is_empty())
+        {
+            return Err(RateParseErr);
+        }
+
+        let whole: i64 = if whole.is_empty() {
+            0
+        } else {
+            whole.parse().map_err(|_| RateParseErr)?
+        };
+        let frac: i64 = format!("{:0<5}", frac).parse().map_err(|_| RateParseErr)?;
+        whole
+            .checked_mul(RATE_SCALE)
+            .and_then(|r| r.checked_add(frac))
+            .filter(|r| *r <= 1000 * RATE_SCALE)
+            .map(Rate)
+            .ok_or(RateParseErr)
+    }
+}
+
+impl Serialize for Rate {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.to_string().trim_end_matches('%'))
+    }
+}
+
+impl<'de> Deserialize<'de> for Rate {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let raw = String::deserialize(deserializer)?;
+        raw.parse()
+            .map_err(|_| de::Error::custom(format!("invalid rate \"{}\"", raw)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{money, rate};
+
+    #[test]
+    fn test_rate_parsing() {
+        assert_eq!(rate("6.5"), Rate(650_000));
+        assert_eq!(rate("6.125%"), Rate(612_500));
+        assert_eq!(rate("0"), Rate(0));
+        assert_eq!(rate("6.5").to_string(), "6.5%");
+        assert_eq!(rate("19").to_string(), "19%");
+        for bad in ["", "-1", "abc", "6.", "1.123456", "1e3", "5000"] {
+            assert!(bad.parse::<Rate>().is_err(), "{}", bad);
+        }
+    }
+
+    #[test]
+    fn test_monthly_interest() {
+        assert_eq!(rate("12").monthly_interest(money("1000")), money("10.00"));
+        assert_eq!(
+            rate("6").monthly_interest(money("200000")),
+            money("1000.00")
+        );
+    }
+}
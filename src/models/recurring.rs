@@ -0,0 +1,486 @@
+use super::account::{self, Account};
+use super::category::Category;
+use super::money::Money;
+use super::transaction::{Occurrence, Transaction, TransactionErr};
+use crate::utils::date_utils::{self, YearMonth};
+use chrono::{Datelike, Duration, NaiveDate};
+use prettytable::{row, Cell, Row, Table};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Frequency {
+    Daily,
+    Weekly,
+    Biweekly,
+    Monthly,
+    Yearly,
+}
+
+impl fmt::Display for Frequency {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Daily => write!(f, "Daily"),
+            Self::Weekly => write!(f, "Weekly"),
+            Self::Biweekly => write!(f, "Biweekly"),
+            Self::Monthly => write!(f, "Monthly"),
+            Self::Yearly => write!(f, "Yearly"),
+        }
+    }
+}
+
+impl std::str::FromStr for Frequency {
+    type Err = RecurringErr;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_uppercase().as_str() {
+            "DAILY" => Ok(Self::Daily),
+            "WEEKLY" => Ok(Self::Weekly),
+            "BIWEEKLY" => Ok(Self::Biweekly),
+            "MONTHLY" => Ok(Self::Monthly),
+            "YEARLY" => Ok(Self::Yearly),
+            _ => Err(RecurringErr::FrequencyParse),
+        }
+    }
+}
+
+/// Which day of the month a monthly or yearly schedule lands on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DayOfMonth {
+    /// This day, or the last day of shorter months.
+    Day(u32),
+    Last,
+}
+
+impl fmt::Display for DayOfMonth {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Day(day) => write!(f, "{}", day),
+            Self::Last => write!(f, "Last"),
+        }
+    }
+}
+
+impl std::str::FromStr for DayOfMonth {
+    type Err = RecurringErr;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.trim().eq_ignore_ascii_case("last") {
+            return Ok(Self::Last);
+        }
+        match s.trim().parse::<u32>() {
+            Ok(day @ 1..=31) => Ok(Self::Day(day)),
+            _ => Err(RecurringErr::DayParse),
+        }
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum RecurringErr {
+    #[error("Invalid frequency, expected daily, weekly, biweekly, monthly or yearly")]
+    FrequencyParse,
+    #[error("Invalid day, expected 1-31 or \"last\"")]
+    DayParse,
+    #[error("--day only applies to monthly and yearly schedules")]
+    DayNotApplicable,
+    #[error("The end date is before the start date")]
+    EndBeforeStart,
+    #[error("No recurring schedule with id {0}")]
+    NotFound(u32),
+    #[error(transparent)]
+    Transaction(#[from] TransactionErr),
+}
+
+/// A transaction that repeats on a schedule until an optional end date.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Schedule {
+    pub id: u32,
+    pub start: NaiveDate,
+    pub frequency: Frequency,
+    /// Only used by monthly and yearly schedules; defaults to the start day.
+    pub day: Option<DayOfMonth>,
+    pub end: Option<NaiveDate>,
+    pub amount: Money,
+    pub category: Category,
+    pub note: Option<String>,
+    pub account: Option<String>,
+    /// Date of the latest occurrence turned into a transaction.
+    pub last_posted: Option<NaiveDate>,
+}
+
+impl Schedule {
+    pub fn new(
+        id: u32,
+        start: NaiveDate,
+        frequency: Frequency,
+        day: Option<DayOfMonth>,
+        end: Option<NaiveDate>,
+        amount: Money,
+        category: Category,
+    ) -> Result<Self, RecurringErr> {
+        if day.is_some() && !matches!(frequency, Frequency::Monthly | Frequency::Yearly) {
+            return Err(RecurringErr::DayNotApplicable);
+        }
+        if end.is_some_and(|end| end < start) {
+            return Err(RecurringErr::EndBeforeStart);
+        }
+        if amount <= Money::ZERO {
+            return Err(TransactionErr::NonPositiveAmount.into());
+        }
+
+        Ok(Schedule {
+            id,
+            start,
+            frequency,
+            day,
+            end,
+            amount,
+            category,
+            note: None,
+            account: None,
+            last_posted: None,
+        })
+    }
+
+    /// The `n`th date in the schedule's pattern, counting from the start.
+    /// Monthly and yearly dates in the start month can fall before `start`.
+    fn nth(&self, n: u32) -> Option<NaiveDate> {
+        let day = match self.day {
+            Some(DayOfMonth::Day(day)) => day,
+            Some(DayOfMonth::Last) => 31,
+            None => self.start.day(),
+        };
+        match self.frequency {
+            Frequency::Daily => self.start.checked_add_signed(Duration::days(n.into())),
+            Frequency::Weekly => self.start.checked_add_signed(Duration::weeks(n.into())),
+            Frequency::Biweekly => self.start.checked_add_signed(Duration::weeks(2 * i64::from(n))),
+            Frequency::Monthly => date_utils::shift_months(self.start, n, day),
+            Frequency::Yearly => date_utils::shift_months(self.start, n.checked_mul(12)?, day),
+        }
+    }
+
+    /// Every occurrence in `from..=to`, clipped to the schedule's own range.
+    pub fn occurrences_between(&self, from: NaiveDate, to: NaiveDate) -> Vec<NaiveDate> {
+        let to = match self.end {
+            Some(end) => to.min(end),
+            None => to,
+        };
+        let from = from.max(self.start);
+
+        let mut dates = Vec::new();
+        for n in 0.. {
+            let Some(date) = self.nth(n) else { break };
+            if date > to {
+                break;
+            }
+            if date >= from {
+                dates.push(date);
+            }
+        }
+        dates
+    }
+
+    /// Occurrences after the last posted one, up to and including `today`.
+    pub fn due(&self, today: NaiveDate) -> Vec<NaiveDate> {
+        let from = match self.last_posted {
+            Some(last) => last.succ_opt().unwrap_or(last),
+            None => self.start,
+        };
+        self.occurrences_between(from, today)
+    }
+
+    /// The schedule posts to an account that has since been closed.
+    pub fn account_closed(&self, accounts: &[Account]) -> bool {
+        self.account
+            .as_deref()
+            .and_then(|name| account::find(accounts, name))
+            .is_some_and(|a| a.closed)
+    }
+
+    /// The first occurrence after `today` that hasn't been posted.
+    pub fn next_due(&self, today: NaiveDate) -> Option<NaiveDate> {
+        let after = self.last_posted.map_or(today, |last| last.max(today));
+        let from = after.succ_opt()?;
+        self.occurrences_between(from, from + Duration::days(366))
+            .first()
+            .copied()
+    }
+}
+
+/// Turn every due occurrence into a transaction, skipping any occurrence
+/// already present in the ledger or trash (`posted`), so running it again
+/// never duplicates anything. `next_id` supplies transaction ids.
+///
+/// Schedules whose account has been closed are left alone, so nothing is
+/// posted to it. Occurrences in a `closed` month are skipped for good.
+pub fn post_due(
+    schedules: &mut [Schedule],
+    posted: &HashSet<Occurrence>,
+    accounts: &[Account],
+    closed: &[YearMonth],
+    today: NaiveDate,
+    mut next_id: impl FnMut() -> u32,
+) -> Vec<Transaction> {
+    let mut created = Vec::new();
+
+    for schedule in schedules.iter_mut() {
+        if schedule.account_closed(accounts) {
+            continue;
+        }
+        let due = schedule.due(today);
+        for date in &due {
+            let occurrence = Occurrence {
+                schedule: schedule.id,
+                date: *date,
+            };
+            if posted.contains(&occurrence) || closed.contains(&YearMonth::of(*date)) {
+                continue;
+            }
+            created.push(
+                Transaction::from_parts(
+                    next_id(),
+                    *date,
+                    schedule.amount,
+                    schedule.category.clone(),
+                    schedule.note.clone(),
+                )
+                .with_account(schedule.account.clone())
+                .with_occurrence(occurrence),
+            );
+        }
+        if let Some(last) = due.last() {
+            schedule.last_posted = Some(*last);
+        }
+    }
+
+    created
+}
+
+pub fn remove(schedules: &mut Vec<Schedule>, id: u32) -> Result<Schedule, RecurringErr> {
+    let pos = schedules
+        .iter()
+        .position(|s| s.id == id)
+        .ok_or(RecurringErr::NotFound(id))?;
+    Ok(schedules.remove(pos))
+}
+
+pub fn print_schedules(schedules: &[Schedule], today: NaiveDate) {
+    let mut table = Table::new();
+
+    table.add_row(row![
+        "ID", "Amount", "Category", "Every", "Day", "Start", "Until", "Next Due", "Account", "Notes"
+    ]);
+
+    for schedule in schedules {
+        let day = match (schedule.frequency, schedule.day) {
+            (Frequency::Monthly | Frequency::Yearly, Some(day)) => day.to_string(),
+            (Frequency::Monthly | Frequency::Yearly, None) => schedule.start.day().to_string(),
+            _ => String::new(),
+        };
+        table.add_row(Row::new(vec![
+            Cell::new(&schedule.id.to_string()),
+            Cell::new(&format!("${}", schedule.amount)),
+            Cell::new(&schedule.category.to_string()),
+            Cell::new(&schedule.frequency.to_string()),
+            Cell::new(&day),
+            Cell::new(&schedule.start.to_string()),
+            Cell::new(&schedule.end.map(|d| d.to_string()).unwrap_or_default()),
+            Cell::new(
+                &schedule
+                    .next_due(today)
+                    .map(|d| d.to_string())
+                    .unwrap_or_else(|| "Ended".to_string()),
+            ),
+            Cell::new(schedule.account.as_deref().unwrap_or("")),
+            Cell::new(schedule.note.as_deref().unwrap_or("N/A")),
+        ]));
+    }
+    table.printstd();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::account::AccountKind;
+    use crate::test_util::{day, money, month};
+
+    fn schedule(start: NaiveDate, frequency: Frequency, rule: Option<DayOfMonth>) -> Schedule {
+        Schedule::new(
+            1,
+            start,
+            frequency,
+            rule,
+            None,
+            money("1200.00"),
+            Category::Bills,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_parse_frequency_and_day() {
+        assert_eq!("Biweekly".parse::<Frequency>().unwrap(), Frequency::Biweekly);
+        assert!("fortnightly".parse::<Frequency>().is_err());
+        assert_eq!("last".parse::<DayOfMonth>().unwrap(), DayOfMonth::Last);
+        assert_eq!("15".parse::<DayOfMonth>().unwrap(), DayOfMonth::Day(15));
+        assert!("0".parse::<DayOfMonth>().is_err());
+        assert!("32".parse::<DayOfMonth>().is_err());
+    }
+
+    #[test]
+    fn test_day_rule_only_for_monthly_and_yearly() {
+        let result = Schedule::new(
+            1,
+            day(2024, 1, 1),
+            Frequency::Weekly,
+            Some(DayOfMonth::Day(3)),
+            None,
+            Money::ZERO,
+            Category::Bills,
+        );
+        assert!(matches!(result, Err(RecurringErr::DayNotApplicable)));
+    }
+
+    #[test]
+    fn test_biweekly_occurrences() {
+        let s = schedule(day(2024, 1, 5), Frequency::Biweekly, None);
+        assert_eq!(
+            s.occurrences_between(day(2024, 1, 1), day(2024, 2, 10)),
+            vec![day(2024, 1, 5), day(2024, 1, 19), day(2024, 2, 2)]
+        );
+    }
+
+    #[test]
+    fn test_monthly_last_day() {
+        let s = schedule(day(2024, 1, 10), Frequency::Monthly, Some(DayOfMonth::Last));
+        assert_eq!(
+            s.occurrences_between(day(2024, 1, 1), day(2024, 4, 30)),
+            vec![day(2024, 1, 31), day(2024, 2, 29), day(2024, 3, 31), day(2024, 4, 30)]
+        );
+    }
+
+    #[test]
+    fn test_monthly_day_before_start_skips_first_month() {
+        let s = schedule(day(2024, 1, 20), Frequency::Monthly, Some(DayOfMonth::Day(15)));
+        assert_eq!(
+            s.occurrences_between(day(2024, 1, 1), day(2024, 3, 31)),
+            vec![day(2024, 2, 15), day(2024, 3, 15)]
+        );
+    }
+
+    #[test]
+    fn test_monthly_31st_keeps_day_after_short_month() {
+        let s = schedule(day(2024, 1, 31), Frequency::Monthly, None);
+        assert_eq!(
+            s.occurrences_between(day(2024, 1, 1), day(2024, 3, 31)),
+            vec![day(2024, 1, 31), day(2024, 2, 29), day(2024, 3, 31)]
+        );
+    }
+
+    #[test]
+    fn test_yearly_and_end_date() {
+        let mut s = schedule(day(2020, 2, 29), Frequency::Yearly, None);
+        s.end = Some(day(2023, 1, 1));
+        assert_eq!(
+            s.occurrences_between(day(2020, 1, 1), day(2030, 1, 1)),
+            vec![day(2020, 2, 29), day(2021, 2, 28), day(2022, 2, 28)]
+        );
+    }
+
+    #[test]
+    fn test_post_due_is_idempotent() {
+        let mut schedules = vec![schedule(day(2024, 1, 1), Frequency::Monthly, None)];
+        let mut next = 100;
+        let mut ids = || {
+            next += 1;
+            next
+        };
+
+        let first = post_due(&mut schedules, &HashSet::new(), &[], &[], day(2024, 3, 15), &mut ids);
+        assert_eq!(first.len(), 3);
+        assert_eq!(schedules[0].last_posted, Some(day(2024, 3, 1)));
+
+        let again = post_due(&mut schedules, &HashSet::new(), &[], &[], day(2024, 3, 15), &mut ids);
+        assert!(again.is_empty());
+
+        let later = post_due(&mut schedules, &HashSet::new(), &[], &[], day(2024, 4, 1), &mut ids);
+        assert_eq!(later.len(), 1);
+        assert_eq!(later[0].date(), day(2024, 4, 1));
+        assert_eq!(later[0].id(), 104);
+    }
+
+    #[test]
+    fn test_post_due_skips_occurrences_already_in_ledger() {
+        // e.g. the ledger was saved but the schedule file wasn't
+        let mut schedules = vec![schedule(day(2024, 1, 1), Frequency::Monthly, None)];
+        let posted: HashSet<Occurrence> = [Occurrence {
+            schedule: 1,
+            date: day(2024, 1, 1),
+        }]
+        .into_iter()
+        .collect();
+
+        let created = post_due(&mut schedules, &posted, &[], &[], day(2024, 2, 1), || 7);
+        assert_eq!(created.len(), 1);
+        assert_eq!(created[0].date(), day(2024, 2, 1));
+    }
+
+    #[test]
+    fn test_post_due_skips_closed_accounts() {
+        let mut closed = Account::new("Old", AccountKind::Checking, Money::ZERO, "USD").unwrap();
+        closed.closed = true;
+        let mut schedules = vec![schedule(day(2024, 1, 1), Frequency::Monthly, None)];
+        schedules[0].account = Some("old".to_string());
+
+        let created = post_due(
+            &mut schedules,
+            &HashSet::new(),
+            &[closed],
+            &[],
+            day(2024, 2, 1),
+            || 7,
+        );
+        assert!(created.is_empty());
+        assert_eq!(schedules[0].last_posted, None);
+    }
+
+    #[test]
+    fn test_post_due_skips_closed_months() {
+        let mut schedules = vec![schedule(day(2024, 1, 1), Frequency::Monthly, None)];
+        let closed = [month("2024-01")];
+        let today = day(2024, 2, 1);
+
+        let created = post_due(&mut schedules, &HashSet::new(), &[], &closed, today, || 7);
+        assert_eq!(created.len(), 1);
+        assert_eq!(created[0].date(), day(2024, 2, 1));
+        assert_eq!(schedules[0].last_posted, Some(day(2024, 2, 1)));
+    }
+
+    #[test]
+    fn test_new_rejects_non_positive_amount() {
+        let result = Schedule::new(
+            1,
+            day(2024, 1, 1),
+            Frequency::Monthly,
+            None,
+            None,
+            Money::ZERO,
+            Category::Food,
+        );
+        assert!(matches!(
+            result,
+            Err(RecurringErr::Transaction(TransactionErr::NonPositiveAmount))
+        ));
+    }
+
+    #[test]
+    fn test_next_due() {
+        let mut s = schedule(day(2024, 1, 15), Frequency::Monthly, None);
+        assert_eq!(s.next_due(day(2024, 1, 10)), Some(day(2024, 1, 15)));
+        s.last_posted = Some(day(2024, 1, 15));
+        assert_eq!(s.next_due(day(2024, 1, 20)), Some(day(2024, 2, 15)));
+        s.end = Some(day(2024, 2, 1));
+        assert_eq!(s.next_due(day(2024, 1, 20)), None);
+    }
+}
@@ -0,0 +1,286 @@
+use super::money::Money;
+use super::transaction::Transaction;
+use crate::utils::date_utils::YearMonth;
+use prettytable::{row, Cell, Row, Table};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, thiserror::Error)]
+pub enum SinkingFundErr {
+    #[error("No sinking fund named \"{0}\"")]
+    NotFound(String),
+    #[error("A sinking fund named \"{0}\" already exists")]
+    AlreadyExists(String),
+    #[error("Invalid due month, expected 1-12 or a month name")]
+    DueMonthParse,
+    #[error("A sinking fund's annual amount must be more than zero")]
+    NonPositiveAmount,
+    #[error("Only expenses can be drawn from a sinking fund")]
+    NotExpense,
+    #[error("A sinking fund needs a name")]
+    EmptyName,
+}
+
+/// Money set aside every month for an expense that comes due once a year.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SinkingFund {
+    pub name: String,
+    pub annual_amount: Money,
+    /// Month of the year (1-12) the expense is paid
+    pub due_month: u32,
+    /// First month money was set aside
+    pub start: YearMonth,
+}
+
+impl SinkingFund {
+    pub fn new(
+        name: &str,
+        annual_amount: Money,
+        due_month: u32,
+        start: YearMonth,
+    ) -> Result<Self, SinkingFundErr> {
+        if name.trim().is_empty() {
+            return Err(SinkingFundErr::EmptyName);
+        }
+        if annual_amount <= Money::ZERO {
+            return Err(SinkingFundErr::NonPositiveAmount);
+        }
+        if !(1..=12).contains(&due_month) {
+            return Err(SinkingFundErr::DueMonthParse);
+        }
+        Ok(SinkingFund {
+            name: name.trim().to_string(),
+            annual_amount,
+            due_month,
+            start,
+        })
+    }
+
+    pub fn monthly_set_aside(&self) -> Money {
+        self.annual_amount.mul_ratio(1, 12)
+    }
+
+    /// Payments drawn from this fund up to the end of `month`.
+    pub fn drawn(&self, transactions: &[Transaction], month: YearMonth) -> Money {
+        transactions
+            .iter()
+            .filter(|t| YearMonth::of(t.date()) <= month)
+            .filter(|t| t.fund().is_some_and(|f| f.eq_ignore_ascii_case(&self.name)))
+            .map(|t| t.amount())
+            .sum()
+    }
+
+    /// Set-asides made from `start` through `month`, less payments drawn.
+    pub fn accumulated(&self, transactions: &[Transaction], month: YearMonth) -> Money {
+        let months = (self.start.months_until(month) + 1).max(0);
+        self.annual_amount.mul_ratio(months as i64, 12) - self.drawn(transactions, month)
+    }
+
+    /// What the fund should hold at the end of `month` to cover the next
+    /// payment: one twelfth of the annual amount for each month since the
+    /// last due month. Once this cycle's payment has been drawn nothing more
+    /// is needed until the next cycle starts.
+    pub fn needed(&self, transactions: &[Transaction], month: YearMonth) -> Money {
+        let into_cycle = (month.month + 11 - self.due_month) % 12 + 1;
+        let before_cycle = month.add_months(-(into_cycle as i32));
+        if self.drawn(transactions, month) > self.drawn(transactions, before_cycle) {
+            return Money::ZERO;
+        }
+        self.annual_amount.mul_ratio(into_cycle as i64, 12)
+    }
+
+    /// The first due month on or after `month`.
+    pub fn next_due(&self, month: YearMonth) -> YearMonth {
+        let year = if self.due_month >= month.month {
+            month.year
+        } else {
+            month.year + 1
+        };
+        YearMonth {
+            year,
+            month: self.due_month,
+        }
+    }
+}
+
+/// Parse a due month given as 1-12 or a month name such as "nov".
+pub fn parse_due_month(raw: &str) -> Result<u32, SinkingFundErr> {
+    let raw = raw.trim();
+    if let Ok(month) = raw.parse::<u32>() {
+        return match month {
+            1..=12 => Ok(month),
+            _ => Err(SinkingFundErr::DueMonthParse),
+        };
+    }
+    raw.parse::<chrono::Month>()
+        .map(|m| m.number_from_month())
+        .map_err(|_| SinkingFundErr::DueMonthParse)
+}
+
+pub fn find<'a>(funds: &'a [SinkingFund], name: &str) -> Result<&'a SinkingFund, SinkingFundErr> {
+    funds
+        .iter()
+        .find(|f| f.name.eq_ignore_ascii_case(name.trim()))
+        .ok_or_else(|| SinkingFundErr::NotFound(name.trim().to_string()))
+}
+
+pub fn add(funds: &mut Vec<SinkingFund>, fund: SinkingFund) -> Result<(), SinkingFundErr> {
+    if find(funds, &fund.name).is_ok() {
+        return Err(SinkingFundErr::AlreadyExists(fund.name));
+    }
+    funds.push(fund);
+    Ok(())
+}
+
+pub fn remove(funds: &mut Vec<SinkingFund>, name: &str) -> Result<(), SinkingFundErr> {
+    let name = find(funds, name)?.name.clone();
+    funds.retain(|f| f.name != name);
+    Ok(())
+}
+
+pub fn print_funds(funds: &[SinkingFund], transactions: &[Transaction], month: YearMonth) {
+    let mut table = Table::new();
+
+    table.add_row(row![
+        "Name",
+        "Annual",
+        "Next Due",
+        "Per Month",
+        "Accumulated",
+        "Needed",
+        "Status"
+    ]);
+
+    for fund in funds {
+        let accumulated = fund.accumulated(transactions, month);
+        let needed = fund.needed(transactions, month);
+        let status = if accumulated >= needed {
+            Cell::new("Funded")
+        } else {
+            Cell::new(&format!("Short by ${}", needed - accumulated)).style_spec("Fr")
+        };
+
+        table.add_row(Row::new(vec![
+            Cell::new(&fund.name),
+            Cell::new(&format!("${}", fund.annual_amount)),
+            Cell::new(&fund.next_due(month).to_string()),
+            Cell::new(&format!("${}", fund.monthly_set_aside())),
+            Cell::new(&format!("${}", accumulated)),
+            Cell::new(&format!("${}", needed)),
+            status,
+        ]));
+    }
+
+    println!("Sinking funds as of {}", month);
+    table.printstd();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{money, month, transaction};
+
+    fn insurance(start: &str) -> SinkingFund {
+        SinkingFund::new("Car insurance", money("1200"), 11, month(start)).unwrap()
+    }
+
+    #[test]
+    fn test_parse_due_month() {
+        assert_eq!(parse_due_month("11").unwrap(), 11);
+        assert_eq!(parse_due_month("nov").unwrap(), 11);
+        assert_eq!(parse_due_month("March").unwrap(), 3);
+        assert!(parse_due_month("13").is_err());
+        assert!(parse_due_month("later").is_err());
+    }
+
+    #[test]
+    fn test_new_validates() {
+        assert!(matches!(
+            SinkingFund::new("Tax", Money::ZERO, 4, month("2024-01")),
+            Err(SinkingFundErr::NonPositiveAmount)
+        ));
+        assert!(matches!(
+            SinkingFund::new("Tax", money("10"), 0, month("2024-01")),
+            Err(SinkingFundErr::DueMonthParse)
+        ));
+        assert!(matches!(
+            SinkingFund::new("  ", money("10"), 4, month("2024-01")),
+            Err(SinkingFundErr::EmptyName)
+        ));
+    }
+
+    #[test]
+    fn test_needed_follows_the_cycle() {
+        let fund = insurance("2023-12");
+        assert_eq!(fund.monthly_set_aside(), money("100"));
+        assert_eq!(fund.needed(&[], month("2023-12")), money("100"));
+        assert_eq!(fund.needed(&[], month("2024-05")), money("600"));
+        assert_eq!(fund.needed(&[], month("2024-11")), money("1200"));
+    }
+
+    #[test]
+    fn test_late_start_is_short() {
+        let fund = insurance("2024-06");
+        let august = month("2024-08");
+        assert_eq!(fund.accumulated(&[], august), money("300"));
+        assert_eq!(fund.needed(&[], august), money("900"));
+    }
+
+    #[test]
+    fn test_draws_reduce_accumulated() {
+        let fund = insurance("2023-12");
+        let payment = transaction(1, "11/20/2024", "1150", "Automotive")
+            .with_fund(Some("car INSURANCE".to_string()));
+        let transactions = [payment];
+
+        assert_eq!(
+            fund.accumulated(&transactions, month("2024-10")),
+            money("1100")
+        );
+        assert_eq!(
+            fund.accumulated(&transactions, month("2024-11")),
+            money("50")
+        );
+        assert_eq!(
+            fund.accumulated(&transactions, month("2024-12")),
+            money("150")
+        );
+    }
+
+    #[test]
+    fn test_cycle_restarts_after_payment() {
+        let fund = insurance("2023-12");
+        let payment = transaction(1, "11/20/2024", "1150", "Automotive")
+            .with_fund(Some("Car insurance".to_string()));
+        let transactions = [payment];
+
+        let november = month("2024-11");
+        assert_eq!(fund.needed(&transactions, month("2024-10")), money("1100"));
+        assert_eq!(fund.needed(&transactions, november), Money::ZERO);
+        assert!(fund.accumulated(&transactions, november) >= fund.needed(&transactions, november));
+        assert_eq!(fund.needed(&transactions, month("2024-12")), money("100"));
+        assert_eq!(fund.needed(&transactions, month("2025-11")), money("1200"));
+    }
+
+    #[test]
+    fn test_next_due() {
+        let fund = insurance("2024-01");
+        assert_eq!(fund.next_due(month("2024-03")), month("2024-11"));
+        assert_eq!(fund.next_due(month("2024-11")), month("2024-11"));
+        assert_eq!(fund.next_due(month("2024-12")), month("2025-11"));
+    }
+
+    #[test]
+    fn test_add_and_remove() {
+        let mut funds = vec![insurance("2024-01")];
+        assert!(matches!(
+            add(&mut funds, insurance("2024-02")),
+            Err(SinkingFundErr::AlreadyExists(_))
+        ));
+        remove(&mut funds, "car insurance").unwrap();
+        assert!(funds.is_empty());
+        assert!(matches!(
+            remove(&mut funds, "Tax"),
+            Err(SinkingFundErr::NotFound(_))
+        ));
+    }
+}
@@ -1,23 +1,92 @@
 use super::category::Category;
+use super::money::Money;
 use crate::storage::file_handler;
 use chrono::NaiveDate;
 use prettytable::{row, Cell, Row, Table};
 use serde;
+use std::collections::HashMap;
 
 #[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
 pub struct Transaction {
     id: u32,
     date: chrono::NaiveDate,
-    amount: f64,
+    amount: Money,
     category: Category,
     note: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    account: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    transfer: Option<TransferLink>,
+    /// Per-category lines of a split transaction; empty for a plain one.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    splits: Vec<Split>,
+    /// Set when the transaction was posted from a recurring schedule.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    recurring: Option<Occurrence>,
+    /// Sinking fund this payment was drawn from; such payments don't count
+    /// against monthly budgets.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    fund: Option<String>,
 }
 
-#[derive(Debug)]
+/// One dated occurrence of a recurring schedule.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+pub struct Occurrence {
+    pub schedule: u32,
+    pub date: NaiveDate,
+}
+
+/// One category's share of a split transaction.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct Split {
+    pub category: Category,
+    pub amount: Money,
+}
+
+/// Which side of a transfer a transaction is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum TransferDirection {
+    Out,
+    In,
+}
+
+/// Ties the two legs of a transfer together by id.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct TransferLink {
+    pub counterpart: u32,
+    pub direction: TransferDirection,
+}
+
+#[derive(Debug, thiserror::Error)]
 pub enum TransactionErr {
+    #[error("Invalid date, expected MM/DD/YYYY")]
     DateParse,
+    #[error("Invalid amount, expected a number with at most two decimal places")]
     AmountParse,
+    #[error("Amount must be greater than zero; use an income category for money coming in")]
+    NonPositiveAmount,
+    #[error("Invalid category")]
     CategoryParse,
+    #[error("No transaction with id {0}")]
+    NotFound(u32),
+    #[error("Transaction id {0} is already in use")]
+    IdInUse(u32),
+    #[error("Transfer amount must be greater than zero")]
+    TransferAmount,
+    #[error("The category of a transfer can't be changed")]
+    TransferCategory,
+    #[error("Invalid split, expected CATEGORY=AMOUNT")]
+    SplitParse,
+    #[error("A split needs at least two lines, each greater than zero")]
+    SplitLines,
+    #[error("Split lines add up to ${actual} but the total is ${expected}")]
+    SplitTotal { expected: Money, actual: Money },
+    #[error("Split lines must be all income or all expense")]
+    SplitMixedTypes,
+    #[error("The amount and category of a split transaction can't be edited")]
+    SplitEdit,
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
 }
 
 impl Transaction {
@@ -29,71 +98,387 @@ impl Transaction {
         note: Option<String>,
     ) -> Result<Self, TransactionErr> {
         let (date, amount, category) = parse_trans(raw_date, raw_amount, raw_cat)?;
-        Ok(Transaction {
+        Ok(Self::from_parts(id, date, amount, category, note))
+    }
+
+    /// Build a plain transaction from values that are already validated.
+    pub fn from_parts(
+        id: u32,
+        date: NaiveDate,
+        amount: Money,
+        category: Category,
+        note: Option<String>,
+    ) -> Self {
+        Transaction {
             id,
             date,
             amount,
             category,
             note,
-        })
+            account: None,
+            transfer: None,
+            splits: Vec::new(),
+            recurring: None,
+            fund: None,
+        }
     }
 
-    pub fn add(
+    /// A transaction whose total is divided across categories. Each raw
+    /// split is `CATEGORY=AMOUNT` and the lines must add up to the total.
+    pub fn new_split(
+        id: u32,
         raw_date: &str,
         raw_amount: &str,
-        raw_cat: &str,
+        raw_splits: &[String],
         note: Option<String>,
-    ) -> Result<(), TransactionErr> {
-        let mut all_trans = file_handler::load_raw().unwrap();
-        let new_trans = Self::new(
-            all_trans.len() as u32 + 1,
-            raw_date,
-            raw_amount,
-            raw_cat,
+    ) -> Result<Self, TransactionErr> {
+        let date = parse_date(raw_date)?;
+        let amount = parse_amount(raw_amount)?;
+        let splits = raw_splits
+            .iter()
+            .map(|raw| parse_split(raw))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        if splits.len() < 2 || splits.iter().any(|s| s.amount <= Money::ZERO) {
+            return Err(TransactionErr::SplitLines);
+        }
+        let income = splits[0].category.is_income();
+        if splits.iter().any(|s| s.category.is_income() != income) {
+            return Err(TransactionErr::SplitMixedTypes);
+        }
+        let actual: Money = splits.iter().map(|s| s.amount).sum();
+        if actual != amount {
+            return Err(TransactionErr::SplitTotal {
+                expected: amount,
+                actual,
+            });
+        }
+
+        Ok(Transaction {
+            id,
+            date,
+            amount,
+            // the parent carries the first line's category so income/expense
+            // checks on the whole transaction stay correct
+            category: splits[0].category.clone(),
             note,
-        )
-        .unwrap();
+            account: None,
+            transfer: None,
+            splits,
+            recurring: None,
+            fund: None,
+        })
+    }
+
+    /// Build the two linked legs of a transfer: money leaving `from` under
+    /// `out_id` and arriving in `to` under `in_id`.
+    pub fn transfer_pair(
+        (out_id, in_id): (u32, u32),
+        date: NaiveDate,
+        amount: Money,
+        (from, to): (&str, &str),
+        note: Option<String>,
+    ) -> Result<(Self, Self), TransactionErr> {
+        if amount <= Money::ZERO {
+            return Err(TransactionErr::TransferAmount);
+        }
+
+        let leg = |id, account: &str, counterpart, direction| Transaction {
+            id,
+            date,
+            amount,
+            category: Category::Transfer,
+            note: note.clone(),
+            account: Some(account.to_string()),
+            transfer: Some(TransferLink {
+                counterpart,
+                direction,
+            }),
+            splits: Vec::new(),
+            recurring: None,
+            fund: None,
+        };
+
+        Ok((
+            leg(out_id, from, in_id, TransferDirection::Out),
+            leg(in_id, to, out_id, TransferDirection::In),
+        ))
+    }
+
+    pub fn with_account(mut self, account: Option<String>) -> Self {
+        self.account = account;
+        self
+    }
+
+    pub fn with_occurrence(mut self, occurrence: Occurrence) -> Self {
+        self.recurring = Some(occurrence);
+        self
+    }
+
+    pub fn with_fund(mut self, fund: Option<String>) -> Self {
+        self.fund = fund;
+        self
+    }
+
+    /// Save this transaction to the ledger under a newly allocated id, which
+    /// replaces whatever id it was built with and is returned.
+    pub fn add(mut self) -> Result<u32, TransactionErr> {
+        let mut all_trans = file_handler::load_raw()?;
+        let mut ids = file_handler::id_allocator(&all_trans)?;
+        self.id = ids.next_id();
+        let id = self.id;
+
+        all_trans.push(self);
+        // persist the counter first so the id is never handed out twice
+        file_handler::save_id_allocator(&ids)?;
+        file_handler::save_raw(&all_trans)?;
+        Ok(id)
+    }
+
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+
+    /// Only meant for data migrations; ids are otherwise fixed at creation.
+    pub fn set_id(&mut self, id: u32) {
+        self.id = id;
+    }
 
-        all_trans.push(new_trans);
-        let _ = file_handler::save_raw(&all_trans);
+    pub fn date(&self) -> NaiveDate {
+        self.date
+    }
+
+    pub fn amount(&self) -> Money {
+        self.amount
+    }
+
+    pub fn category(&self) -> &Category {
+        &self.category
+    }
+
+    pub fn note(&self) -> Option<&str> {
+        self.note.as_deref()
+    }
+
+    pub fn account(&self) -> Option<&str> {
+        self.account.as_deref()
+    }
+
+    pub fn fund(&self) -> Option<&str> {
+        self.fund.as_deref()
+    }
+
+    pub fn transfer(&self) -> Option<TransferLink> {
+        self.transfer
+    }
+
+    pub fn occurrence(&self) -> Option<Occurrence> {
+        self.recurring
+    }
+
+    /// The category/amount pairs this transaction contributes to category
+    /// totals: each split line, or the single category and full amount.
+    pub fn category_lines(&self) -> Vec<(&Category, Money)> {
+        if self.splits.is_empty() {
+            vec![(&self.category, self.amount)]
+        } else {
+            self.splits.iter().map(|s| (&s.category, s.amount)).collect()
+        }
+    }
+
+    /// Category name for display; split transactions list every line's category.
+    pub fn category_label(&self) -> String {
+        if self.splits.is_empty() {
+            self.category.to_string()
+        } else {
+            self.splits
+                .iter()
+                .map(|s| s.category.to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        }
+    }
+
+    /// The amount's effect on an account balance: income and incoming
+    /// transfers add, expenses and outgoing transfers subtract.
+    pub fn signed_amount(&self) -> Money {
+        if let Some(link) = self.transfer {
+            return match link.direction {
+                TransferDirection::In => self.amount,
+                TransferDirection::Out => -self.amount,
+            };
+        }
+        if self.category.is_income() {
+            self.amount
+        } else {
+            -self.amount
+        }
+    }
+
+    /// Replace any of the given fields. Every supplied value is validated
+    /// before anything is changed, so a bad edit leaves the transaction as-is.
+    pub fn edit(
+        &mut self,
+        raw_date: Option<&str>,
+        raw_amount: Option<&str>,
+        raw_cat: Option<&str>,
+        note: Option<String>,
+    ) -> Result<(), TransactionErr> {
+        let date = raw_date.map(parse_date).transpose()?;
+        let amount = raw_amount.map(parse_amount).transpose()?;
+        let category = raw_cat.map(parse_category).transpose()?;
+
+        if !self.splits.is_empty() && (amount.is_some() || category.is_some()) {
+            return Err(TransactionErr::SplitEdit);
+        }
+        if self.transfer.is_some() {
+            if category.is_some() {
+                return Err(TransactionErr::TransferCategory);
+            }
+            if amount.is_some_and(|a| a <= Money::ZERO) {
+                return Err(TransactionErr::TransferAmount);
+            }
+        } else if amount.is_some_and(|a| a <= Money::ZERO) {
+            return Err(TransactionErr::NonPositiveAmount);
+        }
+
+        if let Some(date) = date {
+            self.date = date;
+        }
+        if let Some(amount) = amount {
+            self.amount = amount;
+        }
+        if let Some(category) = category {
+            self.category = category;
+        }
+        if note.is_some() {
+            self.note = note;
+        }
         Ok(())
     }
 }
 
+/// Hands out transaction ids from the persisted counter. Ids are never
+/// reused, even after the transaction that held one is deleted or purged.
+#[derive(Debug)]
+pub struct IdAllocator {
+    next: u32,
+}
+
+impl IdAllocator {
+    /// `counter` is the stored next id; `in_use` guards against files that
+    /// were edited or merged by hand since the counter was last written.
+    pub fn new(counter: u32, in_use: impl IntoIterator<Item = u32>) -> Self {
+        let highest = in_use.into_iter().max().unwrap_or(0);
+        IdAllocator {
+            next: counter.max(highest + 1),
+        }
+    }
+
+    pub fn next_id(&mut self) -> u32 {
+        let id = self.next;
+        self.next += 1;
+        id
+    }
+
+    /// The value to persist as the counter.
+    pub fn counter(&self) -> u32 {
+        self.next
+    }
+}
+
+/// Apply an edit to the transaction with the given id. Editing one leg of
+/// a transfer moves the date and amount of the other leg along with it.
+pub fn edit_by_id(
+    transactions: &mut [Transaction],
+    id: u32,
+    raw_date: Option<&str>,
+    raw_amount: Option<&str>,
+    raw_cat: Option<&str>,
+    note: Option<String>,
+) -> Result<(), TransactionErr> {
+    let edited = transactions
+        .iter_mut()
+        .find(|t| t.id == id)
+        .ok_or(TransactionErr::NotFound(id))?;
+    edited.edit(raw_date, raw_amount, raw_cat, note)?;
+
+    if let Some(link) = edited.transfer {
+        let (date, amount) = (edited.date, edited.amount);
+        if let Some(other) = transactions.iter_mut().find(|t| t.id == link.counterpart) {
+            other.date = date;
+            other.amount = amount;
+        }
+    }
+    Ok(())
+}
+
 fn parse_trans(
     raw_date: &str,
     raw_amount: &str,
     raw_cat: &str,
-) -> Result<(NaiveDate, f64, Category), TransactionErr> {
-    let date =
-        NaiveDate::parse_from_str(raw_date, "%m/%d/%Y").map_err(|_| TransactionErr::DateParse)?;
-    let amount = raw_amount
-        .parse::<f64>()
-        .map_err(|_| TransactionErr::AmountParse)?;
-    let category = raw_cat
-        .parse::<Category>()
-        .map_err(|_| TransactionErr::CategoryParse)?;
+) -> Result<(NaiveDate, Money, Category), TransactionErr> {
+    let date = parse_date(raw_date)?;
+    let amount = parse_amount(raw_amount)?;
+    let category = parse_category(raw_cat)?;
+    if amount <= Money::ZERO {
+        return Err(TransactionErr::NonPositiveAmount);
+    }
 
     Ok((date, amount, category))
 }
 
-pub fn print_transactions(transactions: &Vec<Transaction>) {
+pub fn parse_date(raw_date: &str) -> Result<NaiveDate, TransactionErr> {
+    NaiveDate::parse_from_str(raw_date, "%m/%d/%Y").map_err(|_| TransactionErr::DateParse)
+}
+
+pub fn parse_amount(raw_amount: &str) -> Result<Money, TransactionErr> {
+    raw_amount.parse::<Money>()
+}
+
+pub fn parse_category(raw_cat: &str) -> Result<Category, TransactionErr> {
+    raw_cat
+        .parse::<Category>()
+        .map_err(|_| TransactionErr::CategoryParse)
+}
+
+/// Parse one `CATEGORY=AMOUNT` split line.
+pub fn parse_split(raw: &str) -> Result<Split, TransactionErr> {
+    let (raw_cat, raw_amount) = raw.rsplit_once('=').ok_or(TransactionErr::SplitParse)?;
+    Ok(Split {
+        category: parse_category(raw_cat)?,
+        amount: parse_amount(raw_amount)?,
+    })
+}
+
+/// `balances` holds the formatted running account balance after each
+/// transaction, keyed by id; see [`crate::models::account::balance_labels`].
+pub fn print_transactions(transactions: &[&Transaction], balances: &HashMap<u32, String>) {
+    crate::cli::display::page(&transactions_table(transactions, balances).to_string());
+}
+
+pub fn transactions_table(transactions: &[&Transaction], balances: &HashMap<u32, String>) -> Table {
     let mut table = Table::new();
 
     // Add header row
-    table.add_row(row!["ID", "Amount", "Category", "Date", "Notes"]);
+    table.add_row(row![
+        "ID", "Amount", "Category", "Type", "Date", "Account", "Balance", "Notes"
+    ]);
 
     // Add data rows
     for transaction in transactions {
         table.add_row(Row::new(vec![
             Cell::new(&transaction.id.to_string()),
-            Cell::new(&format!("${:.2}", transaction.amount)),
-            Cell::new(&transaction.category.to_string()),
+            Cell::new(&format!("${}", transaction.amount)),
+            Cell::new(&transaction.category_label()),
+            Cell::new(transaction.category.category_type()),
             Cell::new(&transaction.date.to_string()),
-            Cell::new(&transaction.note.as_ref().unwrap_or(&String::from("N/A"))),
+            Cell::new(transaction.account.as_deref().unwrap_or("")),
+            Cell::new(balances.get(&transaction.id).map_or("", |b| b.as_str())),
+            Cell::new(transaction.note.as_deref().unwrap_or("N/A")),
         ]));
     }
-    table.printstd();
+    table
 }
 
 
@@ -110,7 +495,7 @@ mod tests{
         let parse = parse_trans(raw_date, raw_amount, raw_cat);
 
         let date = NaiveDate::from_ymd_opt(2000,11,30).unwrap();
-        let amount:f64 = 15.00;
+        let amount = "15.00".parse::<Money>().unwrap();
         let cat = Category::Food;
         
         assert_eq!((date, amount, cat), parse.unwrap());
@@ -219,6 +604,16 @@ mod tests{
         }
     }
 
+    #[test]
+    fn test_parse_trans_rejects_non_positive_amount() {
+        for amount in ["-50", "0", "0.00"] {
+            assert!(matches!(
+                parse_trans("01/01/2024", amount, "Food"),
+                Err(TransactionErr::NonPositiveAmount)
+            ));
+        }
+    }
+
     #[test]
     fn test_parse_trans_invalid_category() {
         let result = parse_trans("01/01/2024", "50.00", "InvalidCategory");
@@ -284,4 +679,220 @@ mod tests{
             _ => panic!("Expected Custom category"),
         }
     }
+
+    #[test]
+    fn test_id_allocator_uses_counter() {
+        let mut ids = IdAllocator::new(10, vec![1, 2, 3]);
+        assert_eq!(ids.next_id(), 10);
+        assert_eq!(ids.next_id(), 11);
+        assert_eq!(ids.counter(), 12);
+    }
+
+    #[test]
+    fn test_id_allocator_skips_ids_in_use() {
+        // a stale counter must never hand out an id that already exists
+        let mut ids = IdAllocator::new(2, vec![1, 5, 3]);
+        assert_eq!(ids.next_id(), 6);
+    }
+
+    #[test]
+    fn test_id_allocator_empty() {
+        let mut ids = IdAllocator::new(1, Vec::new());
+        assert_eq!(ids.next_id(), 1);
+    }
+
+    #[test]
+    fn test_edit_updates_only_given_fields() {
+        let mut transaction =
+            Transaction::new(1, "01/15/2024", "25.50", "Food", Some("Lunch".to_string())).unwrap();
+
+        transaction
+            .edit(Some("01/16/2024"), Some("30.00"), None, None)
+            .unwrap();
+
+        assert_eq!(transaction.date, NaiveDate::from_ymd_opt(2024, 1, 16).unwrap());
+        assert_eq!(transaction.amount, "30.00".parse::<Money>().unwrap());
+        assert_eq!(transaction.category, Category::Food);
+        assert_eq!(transaction.note, Some("Lunch".to_string()));
+    }
+
+    #[test]
+    fn test_edit_invalid_value_leaves_transaction_unchanged() {
+        let mut transaction = Transaction::new(1, "01/15/2024", "25.50", "Food", None).unwrap();
+        let original = transaction.clone();
+
+        let result = transaction.edit(Some("02/01/2024"), Some("abc"), None, None);
+
+        assert!(matches!(result, Err(TransactionErr::AmountParse)));
+        assert_eq!(transaction, original);
+    }
+
+    #[test]
+    fn test_edit_rejects_non_positive_amount() {
+        let mut transaction = Transaction::new(1, "01/15/2024", "25.50", "Food", None).unwrap();
+        let original = transaction.clone();
+
+        let result = transaction.edit(None, Some("-25.50"), None, None);
+
+        assert!(matches!(result, Err(TransactionErr::NonPositiveAmount)));
+        assert_eq!(transaction, original);
+    }
+
+    #[test]
+    fn test_edit_by_id_unknown_id() {
+        let mut transactions =
+            vec![Transaction::new(1, "01/15/2024", "25.50", "Food", None).unwrap()];
+
+        let result = edit_by_id(&mut transactions, 7, None, Some("10"), None, None);
+
+        assert!(matches!(result, Err(TransactionErr::NotFound(7))));
+    }
+
+    fn transfer_legs() -> (Transaction, Transaction) {
+        Transaction::transfer_pair(
+            (10, 11),
+            NaiveDate::from_ymd_opt(2024, 3, 1).unwrap(),
+            "250.00".parse().unwrap(),
+            ("Checking", "Savings"),
+            None,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_transfer_pair_is_linked() {
+        let (out, inbound) = transfer_legs();
+
+        assert_eq!(out.account(), Some("Checking"));
+        assert_eq!(inbound.account(), Some("Savings"));
+        assert_eq!(out.transfer().unwrap().counterpart, 11);
+        assert_eq!(inbound.transfer().unwrap().counterpart, 10);
+        assert_eq!(out.signed_amount(), "-250.00".parse::<Money>().unwrap());
+        assert_eq!(inbound.signed_amount(), "250.00".parse::<Money>().unwrap());
+        assert_eq!(out.category().category_type(), "Transfer");
+    }
+
+    #[test]
+    fn test_transfer_pair_rejects_non_positive_amount() {
+        let result = Transaction::transfer_pair(
+            (1, 2),
+            NaiveDate::from_ymd_opt(2024, 3, 1).unwrap(),
+            Money::ZERO,
+            ("Checking", "Savings"),
+            None,
+        );
+
+        assert!(matches!(result, Err(TransactionErr::TransferAmount)));
+    }
+
+    #[test]
+    fn test_edit_transfer_updates_both_legs() {
+        let (out, inbound) = transfer_legs();
+        let mut transactions = vec![out, inbound];
+
+        edit_by_id(&mut transactions, 11, Some("03/02/2024"), Some("300"), None, None).unwrap();
+
+        for leg in &transactions {
+            assert_eq!(leg.date, NaiveDate::from_ymd_opt(2024, 3, 2).unwrap());
+            assert_eq!(leg.amount, "300".parse::<Money>().unwrap());
+        }
+    }
+
+    #[test]
+    fn test_edit_transfer_category_is_refused() {
+        let (out, inbound) = transfer_legs();
+        let mut transactions = vec![out, inbound];
+
+        let result = edit_by_id(&mut transactions, 10, None, None, Some("Food"), None);
+
+        assert!(matches!(result, Err(TransactionErr::TransferCategory)));
+        assert!(transactions[0].category().is_transfer());
+    }
+
+    fn splits(raw: &[&str]) -> Vec<String> {
+        raw.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_new_split_lines_and_label() {
+        let transaction = Transaction::new_split(
+            1,
+            "01/15/2024",
+            "65.50",
+            &splits(&["Food=40", "Shopping=25.5"]),
+            Some("Costco".to_string()),
+        )
+        .unwrap();
+
+        let lines = transaction.category_lines();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0], (&Category::Food, "40".parse::<Money>().unwrap()));
+        assert_eq!(lines[1], (&Category::Shopping, "25.50".parse::<Money>().unwrap()));
+        assert_eq!(transaction.category_label(), "Food, Shopping");
+        assert_eq!(transaction.signed_amount(), "-65.50".parse::<Money>().unwrap());
+    }
+
+    #[test]
+    fn test_plain_transaction_has_one_category_line() {
+        let transaction = Transaction::new(1, "01/15/2024", "25.50", "Food", None).unwrap();
+        assert_eq!(
+            transaction.category_lines(),
+            vec![(&Category::Food, "25.50".parse::<Money>().unwrap())]
+        );
+    }
+
+    #[test]
+    fn test_new_split_must_sum_to_total() {
+        let result = Transaction::new_split(
+            1,
+            "01/15/2024",
+            "70.00",
+            &splits(&["Food=40", "Shopping=25.5"]),
+            None,
+        );
+
+        assert!(matches!(result, Err(TransactionErr::SplitTotal { .. })));
+    }
+
+    #[test]
+    fn test_new_split_rejects_bad_lines() {
+        let one_line = Transaction::new_split(1, "01/15/2024", "40", &splits(&["Food=40"]), None);
+        assert!(matches!(one_line, Err(TransactionErr::SplitLines)));
+
+        let no_amount =
+            Transaction::new_split(1, "01/15/2024", "40", &splits(&["Food", "Bills=0"]), None);
+        assert!(matches!(no_amount, Err(TransactionErr::SplitParse)));
+
+        let mixed = Transaction::new_split(
+            1,
+            "01/15/2024",
+            "60",
+            &splits(&["Food=40", "Salary=20"]),
+            None,
+        );
+        assert!(matches!(mixed, Err(TransactionErr::SplitMixedTypes)));
+    }
+
+    #[test]
+    fn test_parse_split_custom_category() {
+        let split = parse_split("expense:Pet Care=12.50").unwrap();
+        assert_eq!(split.category.to_string(), "Pet Care");
+        assert_eq!(split.amount, "12.50".parse::<Money>().unwrap());
+    }
+
+    #[test]
+    fn test_edit_split_amount_is_refused() {
+        let mut transaction = Transaction::new_split(
+            1,
+            "01/15/2024",
+            "65.50",
+            &splits(&["Food=40", "Shopping=25.5"]),
+            None,
+        )
+        .unwrap();
+
+        let result = transaction.edit(None, Some("70"), None, None);
+        assert!(matches!(result, Err(TransactionErr::SplitEdit)));
+        assert!(transaction.edit(Some("01/16/2024"), None, None, None).is_ok());
+    }
 }
@@ -0,0 +1,228 @@
+use super::transaction::{Transaction, TransactionErr};
+use chrono::NaiveDate;
+use prettytable::{row, Cell, Row, Table};
+use serde::{Deserialize, Serialize};
+
+/// A deleted transaction, kept until it is restored or purged.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TrashEntry {
+    pub transaction: Transaction,
+    pub deleted_on: NaiveDate,
+}
+
+/// Move the transaction with `id` from the ledger into the trash, along
+/// with the other leg if it is a transfer. Returns the ids that were moved.
+pub fn delete(
+    transactions: &mut Vec<Transaction>,
+    trash: &mut Vec<TrashEntry>,
+    id: u32,
+    today: NaiveDate,
+) -> Result<Vec<u32>, TransactionErr> {
+    let target = transactions
+        .iter()
+        .find(|t| t.id() == id)
+        .ok_or(TransactionErr::NotFound(id))?;
+    let ids = with_counterpart(target);
+
+    let (deleted, kept) = std::mem::take(transactions)
+        .into_iter()
+        .partition(|t| ids.contains(&t.id()));
+    *transactions = kept;
+
+    let moved: Vec<u32> = deleted.iter().map(|t| t.id()).collect();
+    trash.extend(deleted.into_iter().map(|transaction| TrashEntry {
+        transaction,
+        deleted_on: today,
+    }));
+    Ok(moved)
+}
+
+/// Move the transaction with `id` from the trash back into the ledger,
+/// along with the other leg if it is a transfer. Each one goes back in front
+/// of the first transaction with a higher id; the rest of the ledger keeps
+/// its order. Returns the ids restored.
+pub fn restore(
+    transactions: &mut Vec<Transaction>,
+    trash: &mut Vec<TrashEntry>,
+    id: u32,
+) -> Result<Vec<u32>, TransactionErr> {
+    let target = trash
+        .iter()
+        .find(|e| e.transaction.id() == id)
+        .ok_or(TransactionErr::NotFound(id))?;
+    let ids = with_counterpart(&target.transaction);
+
+    if let Some(taken) = ids.iter().find(|id| transactions.iter().any(|t| t.id() == **id)) {
+        return Err(TransactionErr::IdInUse(*taken));
+    }
+
+    let (restored, kept) = std::mem::take(trash)
+        .into_iter()
+        .partition(|e| ids.contains(&e.transaction.id()));
+    *trash = kept;
+
+    let moved: Vec<u32> = restored.iter().map(|e| e.transaction.id()).collect();
+    for entry in restored {
+        let at = transactions
+            .iter()
+            .position(|t| t.id() > entry.transaction.id())
+            .unwrap_or(transactions.len());
+        transactions.insert(at, entry.transaction);
+    }
+    Ok(moved)
+}
+
+fn with_counterpart(transaction: &Transaction) -> Vec<u32> {
+    let mut ids = vec![transaction.id()];
+    ids.extend(transaction.transfer().map(|link| link.counterpart));
+    ids
+}
+
+/// Permanently drop entries deleted more than `days` days before `today`.
+/// Returns how many were removed.
+pub fn purge_older_than(trash: &mut Vec<TrashEntry>, days: u32, today: NaiveDate) -> usize {
+    let before = trash.len();
+    trash.retain(|e| (today - e.deleted_on).num_days() <= i64::from(days));
+    before - trash.len()
+}
+
+pub fn print_trash(trash: &[TrashEntry]) {
+    let mut table = Table::new();
+
+    table.add_row(row!["ID", "Amount", "Category", "Date", "Deleted On", "Notes"]);
+
+    for entry in trash {
+        let transaction = &entry.transaction;
+        table.add_row(Row::new(vec![
+            Cell::new(&transaction.id().to_string()),
+            Cell::new(&format!("${}", transaction.amount())),
+            Cell::new(&transaction.category_label()),
+            Cell::new(&transaction.date().to_string()),
+            Cell::new(&entry.deleted_on.to_string()),
+            Cell::new(transaction.note().unwrap_or("N/A")),
+        ]));
+    }
+    table.printstd();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{self, day};
+
+    fn ledger() -> Vec<Transaction> {
+        test_util::ledger(&[
+            ("01/15/2024", "25.50", "Food"),
+            ("01/16/2024", "45.00", "Transport"),
+            ("01/17/2024", "15.00", "Entertainment"),
+        ])
+    }
+
+    #[test]
+    fn test_delete_moves_to_trash() {
+        let mut transactions = ledger();
+        let mut trash = Vec::new();
+
+        delete(&mut transactions, &mut trash, 2, day(2024, 2, 1)).unwrap();
+
+        assert_eq!(transactions.len(), 2);
+        assert!(transactions.iter().all(|t| t.id() != 2));
+        assert_eq!(trash.len(), 1);
+        assert_eq!(trash[0].transaction.id(), 2);
+        assert_eq!(trash[0].deleted_on, day(2024, 2, 1));
+    }
+
+    #[test]
+    fn test_delete_unknown_id() {
+        let mut transactions = ledger();
+        let mut trash = Vec::new();
+
+        let result = delete(&mut transactions, &mut trash, 9, day(2024, 2, 1));
+
+        assert!(matches!(result, Err(TransactionErr::NotFound(9))));
+        assert_eq!(transactions.len(), 3);
+        assert!(trash.is_empty());
+    }
+
+    #[test]
+    fn test_restore_puts_transaction_back_in_order() {
+        let mut transactions = ledger();
+        let original = transactions.clone();
+        let mut trash = Vec::new();
+
+        delete(&mut transactions, &mut trash, 2, day(2024, 2, 1)).unwrap();
+        restore(&mut transactions, &mut trash, 2).unwrap();
+
+        assert_eq!(transactions, original);
+        assert!(trash.is_empty());
+    }
+
+    #[test]
+    fn test_restore_leaves_the_rest_of_the_ledger_alone() {
+        let mut transactions = ledger();
+        transactions.swap(0, 1);
+        let original = transactions.clone();
+        let mut trash = Vec::new();
+
+        delete(&mut transactions, &mut trash, 3, day(2024, 2, 1)).unwrap();
+        restore(&mut transactions, &mut trash, 3).unwrap();
+
+        // ids 2, 1, 3 stay as they were rather than being sorted
+        assert_eq!(transactions, original);
+    }
+
+    #[test]
+    fn test_restore_refuses_id_in_use() {
+        let mut transactions = ledger();
+        let mut trash = vec![TrashEntry {
+            transaction: Transaction::new(3, "01/01/2024", "5.00", "Food", None).unwrap(),
+            deleted_on: day(2024, 2, 1),
+        }];
+
+        let result = restore(&mut transactions, &mut trash, 3);
+
+        assert!(matches!(result, Err(TransactionErr::IdInUse(3))));
+        assert_eq!(trash.len(), 1);
+    }
+
+    #[test]
+    fn test_delete_and_restore_transfer_moves_both_legs() {
+        let (out, inbound) = Transaction::transfer_pair(
+            (4, 5),
+            day(2024, 1, 18),
+            "100.00".parse().unwrap(),
+            ("Checking", "Savings"),
+            None,
+        )
+        .unwrap();
+        let mut transactions = ledger();
+        transactions.push(out);
+        transactions.push(inbound);
+        let original = transactions.clone();
+        let mut trash = Vec::new();
+
+        let moved = delete(&mut transactions, &mut trash, 5, day(2024, 2, 1)).unwrap();
+        assert_eq!(moved, vec![4, 5]);
+        assert_eq!(transactions.len(), 3);
+        assert_eq!(trash.len(), 2);
+
+        let restored = restore(&mut transactions, &mut trash, 4).unwrap();
+        assert_eq!(restored, vec![4, 5]);
+        assert_eq!(transactions, original);
+        assert!(trash.is_empty());
+    }
+
+    #[test]
+    fn test_purge_older_than() {
+        let mut transactions = ledger();
+        let mut trash = Vec::new();
+        delete(&mut transactions, &mut trash, 1, day(2024, 1, 1)).unwrap();
+        delete(&mut transactions, &mut trash, 2, day(2024, 1, 20)).unwrap();
+
+        let removed = purge_older_than(&mut trash, 30, day(2024, 2, 5));
+
+        assert_eq!(removed, 1);
+        assert_eq!(trash.len(), 1);
+        assert_eq!(trash[0].transaction.id(), 2);
+    }
+}
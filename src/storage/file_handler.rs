@@ -1,16 +1,130 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use dirs_next;
-use super::super::models::transaction::Transaction;
+use super::super::models::account::Account;
+use super::super::models::budget::Budget;
+use super::super::models::envelope::Envelope;
+use super::super::models::debt::Debt;
+use super::super::models::goal::Goal;
+use super::super::models::networth::Holding;
+use super::super::models::recurring::Schedule;
+use super::super::models::sinking_fund::SinkingFund;
+use super::super::models::transaction::{IdAllocator, Transaction};
+use super::super::models::trash::TrashEntry;
+use super::super::utils::date_utils::YearMonth;
+use serde::{de::DeserializeOwned, Serialize};
 use serde_json;
 
 
-pub fn data_file_path() -> PathBuf {
+pub fn data_dir() -> PathBuf {
     let base = dirs_next::data_dir()
         .unwrap_or_else(|| {
             dirs_next::home_dir().expect("Could not find a home directory")
         });
 
-    base.join("fincli").join("transaction.json")
+    base.join("fincli")
+}
+
+pub fn data_file_path() -> PathBuf {
+    data_dir().join("transaction.json")
+}
+
+pub fn trash_file_path() -> PathBuf {
+    data_dir().join("trash.json")
+}
+
+pub fn accounts_file_path() -> PathBuf {
+    data_dir().join("accounts.json")
+}
+
+pub fn budgets_file_path() -> PathBuf {
+    data_dir().join("budgets.json")
+}
+
+pub fn envelopes_file_path() -> PathBuf {
+    data_dir().join("envelopes.json")
+}
+
+pub fn closed_months_file_path() -> PathBuf {
+    data_dir().join("closed_months.json")
+}
+
+pub fn goals_file_path() -> PathBuf {
+    data_dir().join("goals.json")
+}
+
+pub fn sinking_funds_file_path() -> PathBuf {
+    data_dir().join("sinking_funds.json")
+}
+
+pub fn debts_file_path() -> PathBuf {
+    data_dir().join("debts.json")
+}
+
+pub fn holdings_file_path() -> PathBuf {
+    data_dir().join("holdings.json")
+}
+
+pub fn recurring_file_path() -> PathBuf {
+    data_dir().join("recurring.json")
+}
+
+pub fn settings_file_path() -> PathBuf {
+    data_dir().join("settings.json")
+}
+
+pub fn quarantine_file_path() -> PathBuf {
+    data_dir().join("quarantine.json")
+}
+
+pub fn meta_file_path() -> PathBuf {
+    data_dir().join("meta.json")
+}
+
+/// Bookkeeping that isn't user data: the data format version and the id counter.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct Meta {
+    pub schema_version: u32,
+    pub next_transaction_id: u32,
+    #[serde(default = "first_id")]
+    pub next_schedule_id: u32,
+}
+
+fn first_id() -> u32 {
+    1
+}
+
+impl Default for Meta {
+    fn default() -> Self {
+        Meta {
+            schema_version: 0,
+            next_transaction_id: first_id(),
+            next_schedule_id: first_id(),
+        }
+    }
+}
+
+/// User preferences changed with the `config` command.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct Settings {
+    /// Percentages of a budget at which `add` warns
+    #[serde(default = "default_thresholds")]
+    pub budget_thresholds: Vec<u32>,
+    /// Whether `add` checks amounts against each category's history
+    #[serde(default)]
+    pub anomaly_check: bool,
+}
+
+fn default_thresholds() -> Vec<u32> {
+    vec![80, 100]
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            budget_thresholds: default_thresholds(),
+            anomaly_check: false,
+        }
+    }
 }
 
 pub fn load_raw() -> Result<Vec<Transaction>, std::io::Error> {
@@ -34,7 +148,190 @@ pub fn save_raw(contents: &Vec<Transaction>) -> Result<(), std::io::Error> {
     std::fs::create_dir_all(dir)?;
     }
 
-    Ok(std::fs::write(path, serde_json::to_string(contents).unwrap())?)
+    std::fs::write(path, serde_json::to_string(contents).unwrap())
+}
+
+/// The entries of a list file as plain JSON, for migrations that have to
+/// read data the current types can't load.
+pub fn load_untyped(path: &Path) -> Result<Vec<serde_json::Value>, std::io::Error> {
+    load_list(path)
+}
+
+pub fn save_untyped(path: &Path, contents: &[serde_json::Value]) -> Result<(), std::io::Error> {
+    save_list(path, contents)
+}
+
+pub fn load_trash() -> Result<Vec<TrashEntry>, std::io::Error> {
+    load_list(&trash_file_path())
+}
+
+pub fn save_trash(contents: &[TrashEntry]) -> Result<(), std::io::Error> {
+    save_list(&trash_file_path(), contents)
+}
+
+pub fn load_accounts() -> Result<Vec<Account>, std::io::Error> {
+    load_list(&accounts_file_path())
+}
+
+pub fn save_accounts(contents: &[Account]) -> Result<(), std::io::Error> {
+    save_list(&accounts_file_path(), contents)
+}
+
+pub fn load_budgets() -> Result<Vec<Budget>, std::io::Error> {
+    load_list(&budgets_file_path())
+}
+
+pub fn save_budgets(contents: &[Budget]) -> Result<(), std::io::Error> {
+    save_list(&budgets_file_path(), contents)
+}
+
+pub fn load_envelopes() -> Result<Vec<Envelope>, std::io::Error> {
+    load_list(&envelopes_file_path())
+}
+
+pub fn save_envelopes(contents: &[Envelope]) -> Result<(), std::io::Error> {
+    save_list(&envelopes_file_path(), contents)
+}
+
+pub fn load_closed_months() -> Result<Vec<YearMonth>, std::io::Error> {
+    load_list(&closed_months_file_path())
+}
+
+pub fn save_closed_months(contents: &[YearMonth]) -> Result<(), std::io::Error> {
+    save_list(&closed_months_file_path(), contents)
+}
+
+pub fn load_goals() -> Result<Vec<Goal>, std::io::Error> {
+    load_list(&goals_file_path())
+}
+
+pub fn save_goals(contents: &[Goal]) -> Result<(), std::io::Error> {
+    save_list(&goals_file_path(), contents)
+}
+
+pub fn load_sinking_funds() -> Result<Vec<SinkingFund>, std::io::Error> {
+    load_list(&sinking_funds_file_path())
+}
+
+pub fn save_sinking_funds(contents: &[SinkingFund]) -> Result<(), std::io::Error> {
+    save_list(&sinking_funds_file_path(), contents)
+}
+
+pub fn load_debts() -> Result<Vec<Debt>, std::io::Error> {
+    load_list(&debts_file_path())
+}
+
+pub fn save_debts(contents: &[Debt]) -> Result<(), std::io::Error> {
+    save_list(&debts_file_path(), contents)
+}
+
+pub fn load_holdings() -> Result<Vec<Holding>, std::io::Error> {
+    load_list(&holdings_file_path())
+}
+
+pub fn save_holdings(contents: &[Holding]) -> Result<(), std::io::Error> {
+    save_list(&holdings_file_path(), contents)
+}
+
+pub fn load_schedules() -> Result<Vec<Schedule>, std::io::Error> {
+    load_list(&recurring_file_path())
+}
+
+pub fn save_schedules(contents: &[Schedule]) -> Result<(), std::io::Error> {
+    save_list(&recurring_file_path(), contents)
+}
+
+pub fn load_settings() -> Result<Settings, std::io::Error> {
+    load_object(&settings_file_path())
+}
+
+pub fn save_settings(settings: &Settings) -> Result<(), std::io::Error> {
+    save_object(&settings_file_path(), settings)
+}
+
+pub fn load_meta() -> Result<Meta, std::io::Error> {
+    load_object(&meta_file_path())
+}
+
+pub fn save_meta(meta: &Meta) -> Result<(), std::io::Error> {
+    save_object(&meta_file_path(), meta)
+}
+
+/// Build an id allocator from the stored counter, taking every id in the
+/// ledger and the trash into account.
+pub fn id_allocator(transactions: &[Transaction]) -> Result<IdAllocator, std::io::Error> {
+    let meta = load_meta()?;
+    let trash = load_trash()?;
+    let in_use = transactions
+        .iter()
+        .map(|t| t.id())
+        .chain(trash.iter().map(|e| e.transaction.id()));
+
+    Ok(IdAllocator::new(meta.next_transaction_id, in_use))
+}
+
+pub fn save_id_allocator(ids: &IdAllocator) -> Result<(), std::io::Error> {
+    let mut meta = load_meta()?;
+    meta.next_transaction_id = ids.counter();
+    save_meta(&meta)
+}
+
+/// Like [`id_allocator`], for recurring schedules. Ids referenced by posted
+/// transactions stay reserved after their schedule is removed.
+pub fn schedule_id_allocator(
+    schedules: &[Schedule],
+    transactions: &[Transaction],
+) -> Result<IdAllocator, std::io::Error> {
+    let meta = load_meta()?;
+    let in_use = schedules
+        .iter()
+        .map(|s| s.id)
+        .chain(transactions.iter().filter_map(|t| t.occurrence()).map(|o| o.schedule));
+
+    Ok(IdAllocator::new(meta.next_schedule_id, in_use))
+}
+
+pub fn save_schedule_id_allocator(ids: &IdAllocator) -> Result<(), std::io::Error> {
+    let mut meta = load_meta()?;
+    meta.next_schedule_id = ids.counter();
+    save_meta(&meta)
+}
+
+/// Read a JSON array from one of the data files. A file that hasn't been
+/// created yet is treated as empty.
+fn load_list<T: DeserializeOwned>(path: &Path) -> Result<Vec<T>, std::io::Error> {
+    load_object(path)
+}
+
+fn save_list<T: Serialize>(path: &Path, contents: &[T]) -> Result<(), std::io::Error> {
+    save_object(path, &contents)
+}
+
+/// Read a single JSON value from one of the data files, falling back to
+/// the default when the file doesn't exist yet.
+fn load_object<T: DeserializeOwned + Default>(path: &Path) -> Result<T, std::io::Error> {
+    let json = match std::fs::read_to_string(path) {
+        Ok(json) => json,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(T::default()),
+        Err(e) => return Err(e),
+    };
+
+    if json.trim().is_empty() {
+        return Ok(T::default());
+    }
+
+    serde_json::from_str::<T>(&json)
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))
+}
+
+fn save_object<T: Serialize + ?Sized>(path: &Path, contents: &T) -> Result<(), std::io::Error> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+
+    let json = serde_json::to_string(contents)
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+    std::fs::write(path, json)
 }
 
 #[cfg(test)]
@@ -62,6 +359,23 @@ mod tests {
         assert!(path.to_string_lossy().contains("fincli"));
     }
 
+    #[test]
+    fn test_trash_file_path() {
+        let path = trash_file_path();
+        assert_eq!(path.parent(), data_file_path().parent());
+        assert_eq!(
+            path.file_name().and_then(|n| n.to_str()),
+            Some("trash.json")
+        );
+    }
+
+    #[test]
+    fn test_load_list_missing_file_is_empty() {
+        let path = std::env::temp_dir().join("fincli-test-missing-file.json");
+        let result: Vec<TrashEntry> = load_list(&path).unwrap();
+        assert!(result.is_empty());
+    }
+
     #[test]
     fn test_save_and_load_transactions() {
         // Create test transactions
@@ -0,0 +1,450 @@
+use super::file_handler::{self, Meta};
+use crate::models::category::Category;
+use crate::models::money::Money;
+use crate::models::transaction::Transaction;
+use crate::models::trash::TrashEntry;
+use serde_json::Value;
+use std::collections::HashSet;
+
+/// Version of the on-disk data format written by this build.
+pub const SCHEMA_VERSION: u32 = 2;
+
+/// Bring the data files up to [`SCHEMA_VERSION`]. Each step runs once; the
+/// returned lines describe anything that was changed.
+pub fn run() -> Result<Vec<String>, std::io::Error> {
+    let mut meta = file_handler::load_meta()?;
+    let mut report = Vec::new();
+
+    if meta.schema_version >= SCHEMA_VERSION {
+        return Ok(report);
+    }
+
+    // amounts go first: every other step loads the files as typed data,
+    // which needs them in the exact format
+    if meta.schema_version < 2 {
+        report.extend(migrate_exact_amounts(&mut meta)?);
+    }
+    if meta.schema_version < 1 {
+        report.extend(migrate_unique_ids(&mut meta)?);
+    }
+
+    meta.schema_version = SCHEMA_VERSION;
+    file_handler::save_meta(&meta)?;
+    Ok(report)
+}
+
+/// Version 1: ids used to be `len + 1`, which repeats after deletes. Give
+/// every duplicate a fresh id and start the persisted counter past them.
+fn migrate_unique_ids(meta: &mut Meta) -> Result<Vec<String>, std::io::Error> {
+    let mut transactions = file_handler::load_raw()?;
+    let mut trash = file_handler::load_trash()?;
+
+    let changes = renumber_duplicates(&mut transactions, &mut trash);
+    if !changes.is_empty() {
+        file_handler::save_raw(&transactions)?;
+        file_handler::save_trash(&trash)?;
+    }
+
+    let highest = transactions
+        .iter()
+        .chain(trash.iter().map(|e| &e.transaction))
+        .map(|t| t.id())
+        .max()
+        .unwrap_or(0);
+    meta.next_transaction_id = meta.next_transaction_id.max(highest + 1);
+
+    Ok(changes
+        .iter()
+        .map(|c| {
+            format!(
+                "Renumbered {}transaction {} -> {} ({}, {}, ${})",
+                if c.in_trash { "trashed " } else { "" },
+                c.old_id,
+                c.new_id,
+                c.date,
+                c.category,
+                c.amount
+            )
+        })
+        .collect())
+}
+
+/// Version 2: amounts were stored as JSON floats. The files are read as
+/// plain JSON so a single bad value can't stop the rest from converting.
+/// Amounts with more than two decimal places are rounded and reported.
+/// Income used to be entered as a negative amount, so negative entries are
+/// turned into positive income and reported; entries that still can't be
+/// loaded are moved to `quarantine.json`.
+fn migrate_exact_amounts(meta: &mut Meta) -> Result<Vec<String>, std::io::Error> {
+    let ledger_path = file_handler::data_file_path();
+    let trash_path = file_handler::trash_file_path();
+    let ledger = convert_amounts(file_handler::load_untyped(&ledger_path)?, false);
+    let trash = convert_amounts(file_handler::load_untyped(&trash_path)?, true);
+
+    let count = ledger.kept.len() + trash.kept.len();
+    if count == 0 && ledger.quarantined.is_empty() && trash.quarantined.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    file_handler::save_untyped(&ledger_path, &ledger.kept)?;
+    file_handler::save_untyped(&trash_path, &trash.kept)?;
+
+    let quarantined: Vec<Value> = ledger
+        .quarantined
+        .into_iter()
+        .chain(trash.quarantined)
+        .collect();
+    if !quarantined.is_empty() {
+        let quarantine_path = file_handler::quarantine_file_path();
+        let mut held = file_handler::load_untyped(&quarantine_path)?;
+        // keep new ids clear of the quarantined ones in case they are restored by hand
+        let highest = quarantined
+            .iter()
+            .filter_map(|entry| entry_id(entry, entry.get("transaction").is_some()))
+            .max()
+            .unwrap_or(0);
+        meta.next_transaction_id = meta.next_transaction_id.max(highest + 1);
+        held.extend(quarantined);
+        file_handler::save_untyped(&quarantine_path, &held)?;
+    }
+
+    let mut report = vec![format!(
+        "Converted {} stored amount(s) to exact decimal format",
+        count
+    )];
+    report.extend(ledger.report);
+    report.extend(trash.report);
+    Ok(report)
+}
+
+/// The outcome of converting one data file's amounts.
+#[derive(Debug, Default)]
+struct Converted {
+    kept: Vec<Value>,
+    quarantined: Vec<Value>,
+    report: Vec<String>,
+}
+
+/// An amount converted from a legacy file, and whether it had to be rounded.
+fn legacy_amount(value: &Value) -> Option<(Money, bool)> {
+    let text = match value {
+        Value::String(s) => s.clone(),
+        Value::Number(n) => n.to_string(),
+        _ => return None,
+    };
+    match text.parse::<Money>() {
+        Ok(amount) => Some((amount, false)),
+        Err(_) => round_to_cents(&text).map(|amount| (amount, true)),
+    }
+}
+
+/// Round a decimal to whole cents, halves away from zero. Works on the
+/// digits rather than the float so `10.005` becomes 10.01, not 10.00.
+fn round_to_cents(text: &str) -> Option<Money> {
+    if text.contains(['e', 'E']) {
+        let value = text.parse::<f64>().ok().filter(|v| v.is_finite())?;
+        let cents = (value * 100.0).round();
+        return (cents.abs() < i64::MAX as f64).then(|| Money::from_cents(cents as i64));
+    }
+
+    let (negative, digits) = match text.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, text),
+    };
+    let (whole, frac) = digits.split_once('.')?;
+    if frac.len() <= 2 || !frac.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let truncated = format!("{}.{}", if whole.is_empty() { "0" } else { whole }, &frac[..2]);
+    let mut amount = truncated.parse::<Money>().ok()?;
+    if frac.as_bytes()[2] >= b'5' {
+        amount = amount.checked_add(Money::from_cents(1))?;
+    }
+    Some(if negative { -amount } else { amount })
+}
+
+/// Legacy files recorded income as a negative amount. Flip the amount and,
+/// if the category was an expense, move it to an income category of the
+/// same name. Returns the category now recorded, or `None` if the stored
+/// category can't be read (the entry is quarantined later).
+fn negative_to_income(transaction: &mut Value, amount: Money) -> Option<String> {
+    let category: Category = serde_json::from_value(transaction.get("category")?.clone()).ok()?;
+    let category = if category.is_income() {
+        category
+    } else {
+        Category::Custom {
+            income_or_expense: "Income".to_string(),
+            name: category.to_string(),
+        }
+    };
+    transaction["category"] = serde_json::to_value(&category).ok()?;
+    transaction["amount"] = Value::String((-amount).to_string());
+    Some(format!("income:{}", category))
+}
+
+fn entry_id(entry: &Value, in_trash: bool) -> Option<u32> {
+    let transaction = if in_trash {
+        entry.get("transaction")?
+    } else {
+        entry
+    };
+    u32::try_from(transaction.get("id")?.as_u64()?).ok()
+}
+
+/// Rewrite every amount in a legacy ledger or trash file as a decimal
+/// string. Only the top-level amount existed before version 2, so split
+/// lines don't need converting.
+fn convert_amounts(entries: Vec<Value>, in_trash: bool) -> Converted {
+    let mut converted = Converted::default();
+    let what = if in_trash {
+        "trashed transaction"
+    } else {
+        "transaction"
+    };
+
+    for mut entry in entries {
+        let id = entry_id(&entry, in_trash)
+            .map(|id| id.to_string())
+            .unwrap_or_else(|| "without an id".to_string());
+        let transaction = if in_trash {
+            entry.get_mut("transaction")
+        } else {
+            Some(&mut entry)
+        };
+        let amount = transaction
+            .as_ref()
+            .and_then(|t| t.get("amount"))
+            .cloned()
+            .unwrap_or(Value::Null);
+
+        let problem = match (transaction, legacy_amount(&amount)) {
+            (Some(transaction), Some((exact, rounded))) => {
+                if rounded {
+                    converted.report.push(format!(
+                        "Rounded the amount of {} {} from {} to {}",
+                        what, id, amount, exact
+                    ));
+                }
+                transaction["amount"] = Value::String(exact.to_string());
+                if exact.is_negative() {
+                    if let Some(category) = negative_to_income(transaction, exact) {
+                        converted.report.push(format!(
+                            "Recorded {} {} as income: {} is now {} in {}",
+                            what, id, exact, -exact, category
+                        ));
+                    }
+                }
+                let loads = if in_trash {
+                    serde_json::from_value::<TrashEntry>(entry.clone()).err()
+                } else {
+                    serde_json::from_value::<Transaction>(entry.clone()).err()
+                };
+                loads.map(|e| e.to_string())
+            }
+            _ => Some(format!("amount {} can't be converted", amount)),
+        };
+
+        match problem {
+            Some(problem) => {
+                converted.report.push(format!(
+                    "Moved {} {} to quarantine.json: {}",
+                    what, id, problem
+                ));
+                converted.quarantined.push(entry);
+            }
+            None => converted.kept.push(entry),
+        }
+    }
+    converted
+}
+
+#[derive(Debug, PartialEq)]
+struct IdChange {
+    old_id: u32,
+    new_id: u32,
+    in_trash: bool,
+    date: chrono::NaiveDate,
+    category: String,
+    amount: Money,
+}
+
+/// The first transaction holding an id keeps it, ledger before trash; every
+/// later holder gets the next id above the current maximum.
+fn renumber_duplicates(
+    transactions: &mut [Transaction],
+    trash: &mut [TrashEntry],
+) -> Vec<IdChange> {
+    let mut next = transactions
+        .iter()
+        .chain(trash.iter().map(|e| &e.transaction))
+        .map(|t| t.id())
+        .max()
+        .unwrap_or(0)
+        + 1;
+    let mut seen = HashSet::new();
+    let mut changes = Vec::new();
+
+    let all = transactions
+        .iter_mut()
+        .map(|t| (t, false))
+        .chain(trash.iter_mut().map(|e| (&mut e.transaction, true)));
+
+    for (transaction, in_trash) in all {
+        if seen.insert(transaction.id()) {
+            continue;
+        }
+
+        changes.push(IdChange {
+            old_id: transaction.id(),
+            new_id: next,
+            in_trash,
+            date: transaction.date(),
+            category: transaction.category_label(),
+            amount: transaction.amount(),
+        });
+        transaction.set_id(next);
+        seen.insert(next);
+        next += 1;
+    }
+
+    changes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::transaction;
+
+    fn trans(id: u32) -> Transaction {
+        transaction(id, "01/15/2024", "25.50", "Food")
+    }
+
+    #[test]
+    fn test_convert_amounts_rounds_and_quarantines() {
+        let v1 = r#"[
+            {"id": 1, "date": "2024-01-05", "amount": 25.5, "category": "Food", "note": null},
+            {"id": 2, "date": "2024-01-06", "amount": 10.005, "category": "Food", "note": null},
+            {"id": 3, "date": "2024-01-07", "amount": null, "category": "Food", "note": null},
+            {"id": 4, "date": "2024-01-08", "amount": 1200, "category": "Salary", "note": "Pay"}
+        ]"#;
+
+        let converted = convert_amounts(serde_json::from_str(v1).unwrap(), false);
+
+        let kept: Vec<Transaction> = converted
+            .kept
+            .into_iter()
+            .map(|v| serde_json::from_value(v).unwrap())
+            .collect();
+        let amounts: Vec<String> = kept.iter().map(|t| t.amount().to_string()).collect();
+        assert_eq!(amounts, ["25.50", "10.01", "1200.00"]);
+        assert_eq!(converted.quarantined.len(), 1);
+        assert_eq!(converted.quarantined[0]["id"], 3);
+        assert_eq!(
+            converted.report,
+            [
+                "Rounded the amount of transaction 2 from 10.005 to 10.01",
+                "Moved transaction 3 to quarantine.json: amount null can't be converted",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_convert_amounts_in_trash() {
+        let v1 = r#"[
+            {"transaction": {"id": 7, "date": "2024-01-05", "amount": -3.14159,
+                             "category": "Food", "note": null},
+             "deleted_on": "2024-02-01"},
+            {"transaction": {"id": 8, "date": "not a date", "amount": 5,
+                             "category": "Food", "note": null},
+             "deleted_on": "2024-02-01"}
+        ]"#;
+
+        let converted = convert_amounts(serde_json::from_str(v1).unwrap(), true);
+
+        assert_eq!(converted.kept[0]["transaction"]["amount"], "3.14");
+        assert_eq!(converted.quarantined[0]["transaction"]["id"], 8);
+        assert_eq!(converted.report.len(), 3);
+        assert!(converted.report[2].starts_with("Moved trashed transaction 8"));
+    }
+
+    #[test]
+    fn test_convert_amounts_turns_negatives_into_income() {
+        let v1 = r#"[
+            {"id": 1, "date": "2024-01-05", "amount": -1200, "category": "Salary", "note": null},
+            {"id": 2, "date": "2024-01-06", "amount": -15.5, "category": "Food", "note": "Refund"}
+        ]"#;
+
+        let converted = convert_amounts(serde_json::from_str(v1).unwrap(), false);
+
+        let kept: Vec<Transaction> = converted
+            .kept
+            .into_iter()
+            .map(|v| serde_json::from_value(v).unwrap())
+            .collect();
+        assert_eq!(kept[0].amount().to_string(), "1200.00");
+        assert_eq!(kept[0].category(), &Category::Salary);
+        assert_eq!(kept[1].amount().to_string(), "15.50");
+        assert!(kept[1].category().is_income());
+        assert_eq!(kept[1].category().to_string(), "Food");
+        assert_eq!(
+            converted.report,
+            [
+                "Recorded transaction 1 as income: -1200.00 is now 1200.00 in income:Salary",
+                "Recorded transaction 2 as income: -15.50 is now 15.50 in income:Food",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_round_to_cents() {
+        let cents = |s: &str| round_to_cents(s).map(|m| m.to_string());
+        assert_eq!(cents("10.005").as_deref(), Some("10.01"));
+        assert_eq!(cents("10.004").as_deref(), Some("10.00"));
+        assert_eq!(cents("-0.125").as_deref(), Some("-0.13"));
+        assert_eq!(cents("1e-7").as_deref(), Some("0.00"));
+        assert_eq!(cents("10.5"), None);
+        assert_eq!(cents("abc"), None);
+    }
+
+    #[test]
+    fn test_renumber_no_duplicates() {
+        let mut transactions = vec![trans(1), trans(2), trans(3)];
+        let mut trash = Vec::new();
+
+        let changes = renumber_duplicates(&mut transactions, &mut trash);
+
+        assert!(changes.is_empty());
+        let ids: Vec<u32> = transactions.iter().map(|t| t.id()).collect();
+        assert_eq!(ids, vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn test_renumber_duplicates_in_ledger() {
+        let mut transactions = vec![trans(1), trans(2), trans(2), trans(3), trans(1)];
+        let mut trash = Vec::new();
+
+        let changes = renumber_duplicates(&mut transactions, &mut trash);
+
+        let ids: Vec<u32> = transactions.iter().map(|t| t.id()).collect();
+        assert_eq!(ids, vec![1, 2, 4, 3, 5]);
+        assert_eq!(changes.len(), 2);
+        assert_eq!((changes[0].old_id, changes[0].new_id), (2, 4));
+        assert_eq!((changes[1].old_id, changes[1].new_id), (1, 5));
+    }
+
+    #[test]
+    fn test_renumber_trash_colliding_with_ledger() {
+        let mut transactions = vec![trans(1), trans(2)];
+        let mut trash = vec![TrashEntry {
+            transaction: trans(2),
+            deleted_on: chrono::NaiveDate::from_ymd_opt(2024, 2, 1).unwrap(),
+        }];
+
+        let changes = renumber_duplicates(&mut transactions, &mut trash);
+
+        assert_eq!(transactions[1].id(), 2);
+        assert_eq!(trash[0].transaction.id(), 3);
+        assert_eq!(changes.len(), 1);
+        assert!(changes[0].in_trash);
+    }
+}
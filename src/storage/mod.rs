@@ -1,3 +1,4 @@
 pub mod encryption;
 pub mod file_handler;
 pub mod backup;
+pub mod migration;
@@ -0,0 +1,46 @@
+//! Builders shared by the unit tests.
+
+use crate::models::category::Category;
+use crate::models::envelope::{self, Envelope};
+use crate::models::money::Money;
+use crate::models::rate::Rate;
+use crate::models::transaction::Transaction;
+use crate::utils::date_utils::YearMonth;
+use chrono::NaiveDate;
+
+pub fn money(s: &str) -> Money {
+    s.parse().unwrap()
+}
+
+pub fn rate(s: &str) -> Rate {
+    s.parse().unwrap()
+}
+
+pub fn month(s: &str) -> YearMonth {
+    s.parse().unwrap()
+}
+
+pub fn day(y: i32, m: u32, d: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(y, m, d).unwrap()
+}
+
+pub fn transaction(id: u32, date: &str, amount: &str, category: &str) -> Transaction {
+    Transaction::new(id, date, amount, category, None).unwrap()
+}
+
+/// Transactions from `(date, amount, category)` rows, numbered from 1.
+pub fn ledger(rows: &[(&str, &str, &str)]) -> Vec<Transaction> {
+    rows.iter()
+        .zip(1..)
+        .map(|(&(date, amount, category), id)| transaction(id, date, amount, category))
+        .collect()
+}
+
+/// Envelopes funded from `(category, month, amount)` rows.
+pub fn funded(rows: &[(Category, &str, &str)]) -> Vec<Envelope> {
+    let mut envelopes = Vec::new();
+    for &(ref category, m, amount) in rows {
+        envelope::fund(&mut envelopes, category.clone(), month(m), money(amount)).unwrap();
+    }
+    envelopes
+}
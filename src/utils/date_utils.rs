@@ -0,0 +1,202 @@
+use chrono::{Datelike, NaiveDate};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// A calendar month, written `YYYY-MM`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(try_from = "StoredYearMonth")]
+pub struct YearMonth {
+    pub year: i32,
+    pub month: u32,
+}
+
+/// A month as stored on disk, checked before it becomes a `YearMonth`.
+#[derive(Deserialize)]
+struct StoredYearMonth {
+    year: i32,
+    month: u32,
+}
+
+impl TryFrom<StoredYearMonth> for YearMonth {
+    type Error = MonthParseErr;
+
+    fn try_from(stored: StoredYearMonth) -> Result<Self, Self::Error> {
+        YearMonth::new(stored.year, stored.month)
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+#[error("Invalid month, expected YYYY-MM")]
+pub struct MonthParseErr;
+
+impl YearMonth {
+    /// The earliest month with a four-digit year.
+    pub const MIN: YearMonth = YearMonth {
+        year: 1000,
+        month: 1,
+    };
+
+    /// A four-digit year and a month from 1 to 12.
+    pub fn new(year: i32, month: u32) -> Result<Self, MonthParseErr> {
+        if year.to_string().len() != 4 || !(1..=12).contains(&month) {
+            return Err(MonthParseErr);
+        }
+        Ok(YearMonth { year, month })
+    }
+
+    pub fn of(date: NaiveDate) -> Self {
+        YearMonth {
+            year: date.year(),
+            month: date.month(),
+        }
+    }
+
+    pub fn first_day(self) -> NaiveDate {
+        NaiveDate::from_ymd_opt(self.year, self.month, 1).expect("valid month")
+    }
+
+    pub fn last_day(self) -> NaiveDate {
+        NaiveDate::from_ymd_opt(self.year, self.month, days_in_month(self.year, self.month))
+            .expect("valid month")
+    }
+
+    pub fn contains(self, date: NaiveDate) -> bool {
+        Self::of(date) == self
+    }
+
+    /// Number of months from `self` to `later`; negative if `later` is
+    /// earlier.
+    pub fn months_until(self, later: YearMonth) -> i32 {
+        (later.year - self.year) * 12 + later.month as i32 - self.month as i32
+    }
+
+    pub fn next(self) -> Self {
+        self.add_months(1)
+    }
+
+    pub fn add_months(self, months: i32) -> Self {
+        let index = self.year * 12 + self.month as i32 - 1 + months;
+        YearMonth {
+            year: index.div_euclid(12),
+            month: index.rem_euclid(12) as u32 + 1,
+        }
+    }
+}
+
+impl fmt::Display for YearMonth {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:04}-{:02}", self.year, self.month)
+    }
+}
+
+impl std::str::FromStr for YearMonth {
+    type Err = MonthParseErr;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (year, month) = s.trim().split_once('-').ok_or(MonthParseErr)?;
+        let year = year.parse::<i32>().map_err(|_| MonthParseErr)?;
+        let month = month.parse::<u32>().map_err(|_| MonthParseErr)?;
+        YearMonth::new(year, month)
+    }
+}
+
+/// Number of days in the given month.
+pub fn days_in_month(year: i32, month: u32) -> u32 {
+    let (next_year, next_month) = if month == 12 {
+        (year + 1, 1)
+    } else {
+        (year, month + 1)
+    };
+    NaiveDate::from_ymd_opt(next_year, next_month, 1)
+        .and_then(|d| d.pred_opt())
+        .map(|d| d.day())
+        .unwrap_or(28)
+}
+
+/// The date `months` months after `date`'s month, on `day`, pulled back to
+/// the last day of the month when the month is too short.
+pub fn shift_months(date: NaiveDate, months: u32, day: u32) -> Option<NaiveDate> {
+    let index = date.year() * 12 + date.month0() as i32 + months as i32;
+    let (year, month) = (index.div_euclid(12), index.rem_euclid(12) as u32 + 1);
+    NaiveDate::from_ymd_opt(year, month, day.min(days_in_month(year, month)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::day;
+
+    #[test]
+    fn test_days_in_month() {
+        assert_eq!(days_in_month(2024, 1), 31);
+        assert_eq!(days_in_month(2024, 2), 29);
+        assert_eq!(days_in_month(2023, 2), 28);
+        assert_eq!(days_in_month(2024, 4), 30);
+        assert_eq!(days_in_month(2024, 12), 31);
+    }
+
+    #[test]
+    fn test_shift_months_across_year() {
+        assert_eq!(shift_months(day(2024, 11, 15), 3, 15), Some(day(2025, 2, 15)));
+    }
+
+    #[test]
+    fn test_shift_months_clamps_to_month_end() {
+        assert_eq!(shift_months(day(2024, 1, 31), 1, 31), Some(day(2024, 2, 29)));
+        assert_eq!(shift_months(day(2024, 1, 31), 2, 31), Some(day(2024, 3, 31)));
+    }
+
+    #[test]
+    fn test_year_month_parse_and_display() {
+        let month: YearMonth = "2024-03".parse().unwrap();
+        assert_eq!(month, YearMonth { year: 2024, month: 3 });
+        assert_eq!(month.to_string(), "2024-03");
+        assert!("2024-13".parse::<YearMonth>().is_err());
+        assert!("2024".parse::<YearMonth>().is_err());
+        assert!("03/2024".parse::<YearMonth>().is_err());
+    }
+
+    #[test]
+    fn test_year_month_deserialize_is_checked() {
+        let month: YearMonth = serde_json::from_str(r#"{"year":2024,"month":3}"#).unwrap();
+        assert_eq!(month, YearMonth { year: 2024, month: 3 });
+        assert!(serde_json::from_str::<YearMonth>(r#"{"year":2024,"month":13}"#).is_err());
+        assert!(serde_json::from_str::<YearMonth>(r#"{"year":2024,"month":0}"#).is_err());
+    }
+
+    #[test]
+    fn test_year_month_contains() {
+        let feb = YearMonth { year: 2024, month: 2 };
+        assert_eq!(feb.first_day(), day(2024, 2, 1));
+        assert_eq!(feb.last_day(), day(2024, 2, 29));
+        assert!(feb.contains(day(2024, 2, 15)));
+        assert!(!feb.contains(day(2024, 3, 1)));
+        assert_eq!(YearMonth::of(day(2024, 7, 4)), YearMonth { year: 2024, month: 7 });
+    }
+
+    #[test]
+    fn test_year_month_add_months() {
+        let march = YearMonth { year: 2024, month: 3 };
+        assert_eq!(march.add_months(-3), YearMonth { year: 2023, month: 12 });
+        assert_eq!(march.add_months(-12), YearMonth { year: 2023, month: 3 });
+        assert_eq!(march.add_months(22), YearMonth { year: 2026, month: 1 });
+    }
+
+    #[test]
+    fn test_year_month_months_until() {
+        let march = YearMonth { year: 2024, month: 3 };
+        assert_eq!(march.months_until(YearMonth { year: 2025, month: 1 }), 10);
+        assert_eq!(march.months_until(march), 0);
+        assert_eq!(march.months_until(YearMonth { year: 2023, month: 12 }), -3);
+    }
+
+    #[test]
+    fn test_year_month_next_wraps_year() {
+        let dec = YearMonth { year: 2024, month: 12 };
+        assert_eq!(dec.next(), YearMonth { year: 2025, month: 1 });
+        assert_eq!(
+            YearMonth { year: 2024, month: 3 }.next(),
+            YearMonth { year: 2024, month: 4 }
+        );
+    }
+}